@@ -0,0 +1,92 @@
+//! Mede o custo por pacote do caminho leitor → tradutor.
+//!
+//! Executar com `cargo bench -p tablet_driver_rust`.
+
+use std::hint::black_box;
+use std::io::Read;
+use std::os::unix::net::UnixStream;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use criterion::{Criterion, criterion_group, criterion_main};
+use table_z_config::Config;
+use tablet_driver_rust::com::socket::{Broadcaster, EmitFeed, SocketServer};
+use tablet_driver_rust::translator::{
+    tablet_m100_translator::TabletM100Translator,
+    translator::{EmitCommand, Translator},
+};
+
+/// Pacote de movimento da caneta com contato (`buf[1] == 193`).
+const PEN_PACKET: [u8; 8] = [0x0a, 193, 0x10, 0x08, 0x20, 0x04, 0x00, 0x02];
+/// Pacote do botão 1 pressionado (`KEY_LEFTCTRL+KEY_Z` na configuração padrão).
+const BTN_PRESS_PACKET: [u8; 8] = [2, 1, 0, 86, 0, 0, 0, 0];
+/// Pacote sem nenhum botão pressionado.
+const BTN_RELEASE_PACKET: [u8; 8] = [2, 0, 0, 0, 0, 0, 0, 0];
+
+fn translator() -> TabletM100Translator {
+    let cfg: Config = serde_yaml::from_str(include_str!("../../table_z_utils.yaml"))
+        .expect("configuração de exemplo inválida");
+    let settings = TabletM100Translator::settings_from_config(&cfg).unwrap();
+    TabletM100Translator::new(Arc::new(ArcSwap::from_pointee(settings)))
+}
+
+/// Inicia o servidor de socket com um cliente conectado que descarta tudo o
+/// que recebe, retornando o canal de broadcast.
+fn connected_broadcaster() -> Broadcaster {
+    let path = std::env::temp_dir().join(format!("tablez_bench_{}.sock", std::process::id()));
+    let server = SocketServer::new(path.to_str().unwrap());
+
+    let mut client = loop {
+        match UnixStream::connect(&path) {
+            Ok(client) => break client,
+            Err(_) => thread::sleep(Duration::from_millis(10)),
+        }
+    };
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while client.read(&mut buf).is_ok_and(|n| n > 0) {}
+    });
+
+    let tx = server.sender();
+    while !tx.has_clients() {
+        thread::sleep(Duration::from_millis(10));
+    }
+    tx
+}
+
+fn bench_conv(c: &mut Criterion) {
+    let mut tr = translator();
+    let mut out: Vec<EmitCommand> = Vec::with_capacity(16);
+
+    c.bench_function("conv/pen", |b| {
+        b.iter(|| {
+            out.clear();
+            tr.conv(black_box(&PEN_PACKET), &mut out);
+            black_box(&out);
+        })
+    });
+
+    c.bench_function("conv/button_press_release", |b| {
+        b.iter(|| {
+            out.clear();
+            tr.conv(black_box(&BTN_PRESS_PACKET), &mut out);
+            tr.conv(black_box(&BTN_RELEASE_PACKET), &mut out);
+            black_box(&out);
+        })
+    });
+
+    // Caminho completo com um cliente conectado ao socket
+    let mut feed = EmitFeed::new(connected_broadcaster());
+    c.bench_function("conv/pen_with_json", |b| {
+        b.iter(|| {
+            out.clear();
+            tr.conv(black_box(&PEN_PACKET), &mut out);
+            feed.send(&out);
+        })
+    });
+}
+
+criterion_group!(benches, bench_conv);
+criterion_main!(benches);
@@ -0,0 +1,232 @@
+//! Reprodução offline de capturas de pacotes (`.tzcap`).
+//!
+//! Lê um arquivo gravado pela captura do driver, passa os pacotes por um
+//! [`Translator`] com a configuração escolhida e imprime os [`EmitCommand`]
+//! resultantes (uma linha JSON por comando, no mesmo formato do socket).
+//!
+//! # Uso
+//! ```text
+//! tablez-replay <captura.tzcap> [opções]
+//!
+//!   --config <arquivo>      Configuração YAML (padrão: /etc/table_z_utils.yaml)
+//!   --translator <nome>     Tradutor a usar (padrão: m100)
+//!   --endpoint <endereço>   Reproduz apenas pacotes deste endpoint (ex: 0x81)
+//!   --diff <arquivo>        Compara a saída com um fluxo JSON esperado
+//!   --pace                  Respeita os intervalos originais entre pacotes
+//!   --emit                  Emite os comandos em dispositivos uinput reais
+//!   --quiet                 Não imprime os comandos
+//! ```
+//!
+//! Com `--diff`, o processo termina com código `1` se houver divergências.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use arc_swap::ArcSwap;
+use table_z_config::Config;
+use tablet_driver_rust::{
+    capture::CaptureReader,
+    translator::{
+        tablet_m100_translator::TabletM100Translator,
+        translator::{EmitCommand, Translator},
+    },
+    virtual_device::EmitDispatcher,
+};
+
+/// Opções de linha de comando.
+struct Options {
+    capture: PathBuf,
+    config: PathBuf,
+    translator: String,
+    endpoint: Option<u8>,
+    diff: Option<PathBuf>,
+    pace: bool,
+    emit: bool,
+    quiet: bool,
+}
+
+impl Options {
+    /// Interpreta os argumentos da linha de comando.
+    fn parse() -> Result<Self> {
+        let mut capture = None;
+        let mut opts = Options {
+            capture: PathBuf::new(),
+            config: PathBuf::from("/etc/table_z_utils.yaml"),
+            translator: "m100".into(),
+            endpoint: None,
+            diff: None,
+            pace: false,
+            emit: false,
+            quiet: false,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => opts.config = args.next().context("--config requer um arquivo")?.into(),
+                "--translator" => {
+                    opts.translator = args.next().context("--translator requer um nome")?
+                }
+                "--endpoint" => {
+                    let value = args.next().context("--endpoint requer um endereço")?;
+                    opts.endpoint = Some(parse_u8(&value)?);
+                }
+                "--diff" => opts.diff = Some(args.next().context("--diff requer um arquivo")?.into()),
+                "--pace" => opts.pace = true,
+                "--emit" => opts.emit = true,
+                "--quiet" => opts.quiet = true,
+                _ if arg.starts_with("--") => bail!("Opção desconhecida: {arg}"),
+                _ => capture = Some(PathBuf::from(arg)),
+            }
+        }
+
+        opts.capture = capture.context("Uso: tablez-replay <captura.tzcap> [opções]")?;
+        Ok(opts)
+    }
+}
+
+/// Converte um número decimal ou hexadecimal (`0x81`) em `u8`.
+fn parse_u8(value: &str) -> Result<u8> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.with_context(|| format!("Endereço inválido: {value}"))
+}
+
+/// Lê um fluxo esperado de comandos (uma linha JSON por comando).
+fn read_expected(path: &Path) -> Result<Vec<EmitCommand>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Erro ao ler {}", path.display()))?;
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("{}:{}: comando inválido", path.display(), n + 1))
+        })
+        .collect()
+}
+
+/// Reproduz a captura com o tradutor informado e retorna os comandos produzidos.
+fn replay<T: Translator>(
+    mut translator: T,
+    opts: &Options,
+    mut dispatcher: Option<EmitDispatcher>,
+) -> Result<Vec<EmitCommand>> {
+    let reader = CaptureReader::open(&opts.capture)?;
+    let mut produced = Vec::new();
+    let mut emit_flow: Vec<EmitCommand> = Vec::with_capacity(16);
+
+    let started = Instant::now();
+    let mut first_timestamp = None;
+
+    for record in reader {
+        let record = record?;
+
+        if opts.endpoint.is_some_and(|ep| ep != record.endpoint) {
+            continue;
+        }
+
+        // Mantém os intervalos originais entre os pacotes
+        if opts.pace {
+            let first = *first_timestamp.get_or_insert(record.timestamp_us);
+            let due = Duration::from_micros(record.timestamp_us.saturating_sub(first));
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+
+        emit_flow.clear();
+        translator.conv(&record.data, &mut emit_flow);
+
+        if let Some(dispatcher) = dispatcher.as_mut() {
+            dispatcher.dispatch(&emit_flow);
+        }
+
+        if !opts.quiet {
+            for emit in &emit_flow {
+                println!("{}", serde_json::to_string(emit)?);
+            }
+        }
+
+        produced.extend_from_slice(&emit_flow);
+    }
+
+    Ok(produced)
+}
+
+/// Compara os comandos produzidos com os esperados, imprimindo as divergências.
+///
+/// Retorna `true` se os fluxos forem idênticos.
+fn diff(produced: &[EmitCommand], expected: &[EmitCommand]) -> bool {
+    let mut equal = true;
+
+    for (n, (got, want)) in produced.iter().zip(expected).enumerate() {
+        if got != want {
+            eprintln!("#{n}: esperado {want:?}, obtido {got:?}");
+            equal = false;
+        }
+    }
+
+    if produced.len() != expected.len() {
+        eprintln!(
+            "Quantidade de comandos diferente: esperado {}, obtido {}",
+            expected.len(),
+            produced.len()
+        );
+        equal = false;
+    }
+
+    equal
+}
+
+fn run() -> Result<bool> {
+    let opts = Options::parse()?;
+    let cfg = Config::from_file(&opts.config).map_err(|e| anyhow::anyhow!(e))?;
+
+    let produced = match opts.translator.as_str() {
+        "m100" => {
+            let settings = TabletM100Translator::settings_from_config(&cfg)?;
+            let dispatcher = if opts.emit {
+                let keys = TabletM100Translator::emitted_keys(&settings);
+                Some(EmitDispatcher::from_config(&cfg, &keys)?)
+            } else {
+                None
+            };
+            let translator = TabletM100Translator::new(Arc::new(ArcSwap::from_pointee(settings)));
+            replay(translator, &opts, dispatcher)?
+        }
+        other => bail!("Tradutor desconhecido: {other} (disponíveis: m100)"),
+    };
+
+    match &opts.diff {
+        Some(path) => {
+            let expected = read_expected(path)?;
+            let equal = diff(&produced, &expected);
+            if equal {
+                eprintln!("Saída idêntica ao esperado ({} comandos).", produced.len());
+            }
+            Ok(equal)
+        }
+        None => Ok(true),
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Erro: {e:#}");
+            ExitCode::from(2)
+        }
+    }
+}
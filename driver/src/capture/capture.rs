@@ -0,0 +1,409 @@
+use anyhow::{bail, Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+use table_z_config::CaptureConfig;
+
+/// Assinatura gravada no início de cada arquivo de captura (inclui a versão do formato).
+pub const CAPTURE_MAGIC: &[u8; 8] = b"TZCAP01\n";
+
+/// Tamanho do cabeçalho de cada registro: timestamp (8) + endpoint (1) + tamanho (2).
+pub const RECORD_HEADER_LEN: usize = 11;
+
+/// Intervalo máximo entre gravações efetivas em disco.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Gravador de pacotes HID crus em arquivo, com limite de tamanho e rotação.
+///
+/// ### Formato
+/// O arquivo começa com [`CAPTURE_MAGIC`], seguido de registros no formato
+/// (inteiros em *little endian*):
+///
+/// | Campo       | Tipo  | Descrição                                              |
+/// |-------------|-------|--------------------------------------------------------|
+/// | `timestamp` | `u64` | Microssegundos desde o início da captura (monotônico)  |
+/// | `endpoint`  | `u8`  | Endereço do endpoint de origem                         |
+/// | `len`       | `u16` | Tamanho do pacote                                      |
+/// | `data`      | bytes | Conteúdo do pacote                                     |
+///
+/// Quando o arquivo atual excederia `max_file_size`, ele é renomeado para
+/// `<path>.1` (e os anteriores deslocados até `<path>.<max_files>`), e um novo
+/// arquivo é iniciado. Os timestamps continuam crescendo entre arquivos.
+pub struct CaptureWriter {
+    /// Caminho do arquivo atual
+    path: PathBuf,
+    /// Tamanho máximo de cada arquivo
+    max_file_size: u64,
+    /// Quantidade de arquivos antigos mantidos
+    max_files: u32,
+    /// Arquivo atual
+    out: BufWriter<File>,
+    /// Bytes gravados no arquivo atual
+    written: u64,
+    /// Início da captura (referência dos timestamps)
+    start: Instant,
+    /// Momento da última gravação efetiva em disco
+    last_flush: Instant,
+}
+
+impl CaptureWriter {
+    /// Cria um novo arquivo de captura, substituindo um existente no mesmo caminho.
+    pub fn create(path: &Path, max_file_size: u64, max_files: u32) -> Result<Self> {
+        let out = Self::open(path)?;
+        let now = Instant::now();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_file_size,
+            max_files,
+            out,
+            written: CAPTURE_MAGIC.len() as u64,
+            start: now,
+            last_flush: now,
+        })
+    }
+
+    /// Abre (truncando) o arquivo e grava a assinatura.
+    ///
+    /// Links simbólicos não são seguidos (`O_NOFOLLOW`): o caminho padrão fica em
+    /// `/tmp`, onde outro usuário poderia apontá-lo para um arquivo do sistema.
+    fn open(path: &Path) -> Result<BufWriter<File>> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .custom_flags(libc::O_NOFOLLOW)
+            .open(path)
+            .with_context(|| format!("Erro ao criar arquivo de captura {}", path.display()))?;
+        let mut out = BufWriter::new(file);
+        out.write_all(CAPTURE_MAGIC)?;
+        Ok(out)
+    }
+
+    /// Caminho do arquivo de índice `n` na rotação (`0` é o arquivo atual).
+    fn rotated_path(&self, n: u32) -> PathBuf {
+        if n == 0 {
+            return self.path.clone();
+        }
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    /// Fecha o arquivo atual, desloca os antigos e inicia um novo.
+    fn rotate(&mut self) -> Result<()> {
+        self.out.flush()?;
+
+        if self.max_files > 0 {
+            for n in (0..self.max_files).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+        }
+
+        self.out = Self::open(&self.path)?;
+        self.written = CAPTURE_MAGIC.len() as u64;
+        Ok(())
+    }
+
+    /// Grava um pacote recebido do `endpoint` informado.
+    pub fn write_packet(&mut self, endpoint: u8, data: &[u8]) -> Result<()> {
+        let data = &data[..data.len().min(u16::MAX as usize)];
+        let record_len = (RECORD_HEADER_LEN + data.len()) as u64;
+
+        if self.written + record_len > self.max_file_size {
+            self.rotate()?;
+        }
+
+        let timestamp = self.start.elapsed().as_micros() as u64;
+        self.out.write_all(&timestamp.to_le_bytes())?;
+        self.out.write_all(&[endpoint])?;
+        self.out.write_all(&(data.len() as u16).to_le_bytes())?;
+        self.out.write_all(data)?;
+        self.written += record_len;
+
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.out.flush()?;
+            self.last_flush = Instant::now();
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for CaptureWriter {
+    fn drop(&mut self) {
+        let _ = self.out.flush();
+    }
+}
+
+/// Captura ativa e a configuração que a originou.
+type ActiveCapture = Option<(CaptureConfig, CaptureWriter)>;
+
+/// Controle compartilhado da captura de pacotes.
+///
+/// Clonável entre threads: o loop principal liga/desliga a captura com
+/// [`PacketCapture::apply`] e as threads de leitura registram os pacotes com
+/// [`PacketCapture::record`]. Com a captura desligada, `record` apenas consulta
+/// uma flag atômica.
+#[derive(Clone, Default)]
+pub struct PacketCapture {
+    /// Indica se há captura ativa
+    active: Arc<AtomicBool>,
+    /// Gravador atual
+    writer: Arc<Mutex<ActiveCapture>>,
+}
+
+impl PacketCapture {
+    /// Aplica a configuração de captura, iniciando, reiniciando ou parando a gravação.
+    ///
+    /// Não faz nada se a configuração não mudou.
+    pub fn apply(&self, cfg: &CaptureConfig) {
+        let mut guard = self.writer.lock().unwrap();
+
+        if !cfg.enabled {
+            if guard.take().is_some() {
+                println!("Captura de pacotes encerrada.");
+            }
+            self.active.store(false, Ordering::SeqCst);
+            return;
+        }
+
+        if matches!(&*guard, Some((current, _)) if current == cfg) {
+            return;
+        }
+
+        match CaptureWriter::create(Path::new(&cfg.path), cfg.max_file_size, cfg.max_files) {
+            Ok(writer) => {
+                println!("Capturando pacotes em {}", cfg.path);
+                *guard = Some((cfg.clone(), writer));
+                self.active.store(true, Ordering::SeqCst);
+            }
+            Err(e) => {
+                eprintln!("Erro ao iniciar captura: {e:#}");
+                *guard = None;
+                self.active.store(false, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Registra um pacote, se a captura estiver ativa.
+    ///
+    /// Em caso de erro de escrita, a captura é desativada.
+    pub fn record(&self, endpoint: u8, data: &[u8]) {
+        if !self.active.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut guard = self.writer.lock().unwrap();
+        if let Some((_, writer)) = guard.as_mut()
+            && let Err(e) = writer.write_packet(endpoint, data)
+        {
+            eprintln!("Erro gravando captura, desativando: {e:#}");
+            *guard = None;
+            self.active.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Pacote lido de um arquivo de captura.
+#[derive(Debug, Clone)]
+pub struct CaptureRecord {
+    /// Microssegundos desde o início da captura
+    pub timestamp_us: u64,
+    /// Endereço do endpoint de origem
+    pub endpoint: u8,
+    /// Conteúdo do pacote
+    pub data: Vec<u8>,
+}
+
+/// Leitor sequencial de arquivos gravados pelo [`CaptureWriter`].
+///
+/// Implementa [`Iterator`], produzindo um [`CaptureRecord`] por pacote.
+/// Um registro truncado no fim do arquivo (captura interrompida) é reportado
+/// como erro, após o qual a leitura termina.
+pub struct CaptureReader {
+    input: BufReader<File>,
+}
+
+impl CaptureReader {
+    /// Abre um arquivo de captura, validando a assinatura.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Erro ao abrir captura {}", path.display()))?;
+        let mut input = BufReader::new(file);
+
+        let mut magic = [0u8; CAPTURE_MAGIC.len()];
+        input
+            .read_exact(&mut magic)
+            .with_context(|| format!("Captura vazia ou inválida: {}", path.display()))?;
+        if &magic != CAPTURE_MAGIC {
+            bail!("Formato de captura não reconhecido: {}", path.display());
+        }
+
+        Ok(Self { input })
+    }
+
+    /// Lê o próximo registro; `Ok(None)` indica fim do arquivo.
+    fn read_record(&mut self) -> Result<Option<CaptureRecord>> {
+        if self.input.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+
+        let mut header = [0u8; RECORD_HEADER_LEN];
+        self.input
+            .read_exact(&mut header)
+            .context("Registro truncado no fim da captura")?;
+
+        let timestamp_us = u64::from_le_bytes(header[0..8].try_into().unwrap());
+        let endpoint = header[8];
+        let len = u16::from_le_bytes([header[9], header[10]]) as usize;
+
+        let mut data = vec![0u8; len];
+        self.input
+            .read_exact(&mut data)
+            .context("Registro truncado no fim da captura")?;
+
+        Ok(Some(CaptureRecord {
+            timestamp_us,
+            endpoint,
+            data,
+        }))
+    }
+}
+
+impl Iterator for CaptureReader {
+    type Item = Result<CaptureRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Diretório temporário removido ao final do teste.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("tablez_capture_{}_{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Lê todos os registros de um arquivo de captura.
+    fn read_all(path: &Path) -> Vec<CaptureRecord> {
+        CaptureReader::open(path)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    /// Tamanho em disco de um registro com `len` bytes de dados.
+    fn record_len(len: usize) -> u64 {
+        (RECORD_HEADER_LEN + len) as u64
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let dir = TempDir::new("round_trip");
+        let path = dir.0.join("capture.bin");
+
+        let mut writer = CaptureWriter::create(&path, 1 << 20, 2).unwrap();
+        writer.write_packet(0x81, &[1, 2, 3]).unwrap();
+        writer.write_packet(0x82, &[]).unwrap();
+        writer.write_packet(0x81, &[0xff; 64]).unwrap();
+        drop(writer);
+
+        let records = read_all(&path);
+        let packets: Vec<(u8, Vec<u8>)> =
+            records.iter().map(|r| (r.endpoint, r.data.clone())).collect();
+        assert_eq!(
+            packets,
+            vec![(0x81, vec![1, 2, 3]), (0x82, vec![]), (0x81, vec![0xff; 64])]
+        );
+        assert!(records.windows(2).all(|w| w[0].timestamp_us <= w[1].timestamp_us));
+    }
+
+    #[test]
+    fn rotates_past_max_file_size() {
+        let dir = TempDir::new("rotate");
+        let path = dir.0.join("capture.bin");
+
+        // Dois registros de 8 bytes por arquivo
+        let max_file_size = CAPTURE_MAGIC.len() as u64 + 2 * record_len(8);
+        let mut writer = CaptureWriter::create(&path, max_file_size, 2).unwrap();
+        for n in 0..7u8 {
+            writer.write_packet(0x81, &[n; 8]).unwrap();
+        }
+        drop(writer);
+
+        // O arquivo mais antigo (pacotes 0 e 1) foi descartado
+        assert!(!dir.0.join("capture.bin.3").exists());
+        let files = ["capture.bin.2", "capture.bin.1", "capture.bin"];
+        for file in files {
+            assert!(fs::metadata(dir.0.join(file)).unwrap().len() <= max_file_size);
+        }
+
+        let records: Vec<CaptureRecord> =
+            files.iter().flat_map(|file| read_all(&dir.0.join(file))).collect();
+        let payloads: Vec<u8> = records.iter().map(|r| r.data[0]).collect();
+        assert_eq!(payloads, vec![2, 3, 4, 5, 6]);
+        assert!(records.iter().all(|r| r.endpoint == 0x81 && r.data.len() == 8));
+        assert!(records.windows(2).all(|w| w[0].timestamp_us <= w[1].timestamp_us));
+    }
+
+    #[test]
+    fn refuses_to_follow_symlink() {
+        let dir = TempDir::new("symlink");
+        let target = dir.0.join("target");
+        let link = dir.0.join("capture.bin");
+        fs::write(&target, b"original").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert!(CaptureWriter::create(&link, 1 << 20, 2).is_err());
+        assert_eq!(fs::read(&target).unwrap(), b"original");
+    }
+
+    #[test]
+    fn truncated_record_is_an_error() {
+        let dir = TempDir::new("truncated");
+        let path = dir.0.join("capture.bin");
+
+        let mut writer = CaptureWriter::create(&path, 1 << 20, 0).unwrap();
+        writer.write_packet(0x81, &[1; 8]).unwrap();
+        writer.write_packet(0x81, &[2; 8]).unwrap();
+        drop(writer);
+
+        let complete = CAPTURE_MAGIC.len() as u64 + record_len(8);
+        // Cortado no meio dos dados e no meio do cabeçalho do segundo registro
+        for len in [complete + record_len(8) - 3, complete + 5] {
+            File::options().write(true).open(&path).unwrap().set_len(len).unwrap();
+
+            let mut reader = CaptureReader::open(&path).unwrap();
+            assert_eq!(reader.next().unwrap().unwrap().data, vec![1; 8]);
+            assert!(reader.next().unwrap().is_err());
+            assert!(reader.next().is_none());
+        }
+    }
+}
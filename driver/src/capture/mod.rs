@@ -0,0 +1,2 @@
+pub mod capture;
+pub use capture::{CaptureReader, CaptureRecord, CaptureWriter, PacketCapture};
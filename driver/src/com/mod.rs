@@ -1 +1,2 @@
-pub mod socket;
\ No newline at end of file
+pub mod protocol;
+pub mod socket;
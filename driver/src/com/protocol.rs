@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use table_z_config::ButtonMapConfig;
+
+use crate::reader::ReaderExit;
+use crate::session::{DeviceKey, SessionInfo};
+use crate::stats::StatsSnapshot;
+
+/// Comandos de controle aceitos pelo socket, além da atualização de configuração.
+///
+/// Enviados como uma linha JSON, por exemplo:
+/// ```json
+/// {"LearnButtons":{"count":8}}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SocketCommand {
+    /// Inicia o modo de aprendizado para `count` botões do tablet.
+    LearnButtons { count: usize },
+    /// Cancela o aprendizado em andamento.
+    CancelLearn,
+    /// Solicita as estatísticas de entrada (respondido com [`DriverEvent::Status`]).
+    Status,
+    /// Lista as sessões ativas (respondido com [`DriverEvent::Sessions`]).
+    ListSessions,
+}
+
+/// Eventos do driver enviados aos clientes do socket (uma linha JSON cada),
+/// intercalados com os [`EmitCommand`](crate::translator::translator::EmitCommand).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DriverEvent {
+    /// Solicita que o usuário pressione o botão `button` (a partir de 1).
+    LearnPrompt { button: usize, total: usize },
+    /// O pacote recebido é igual ao de um botão já aprendido.
+    LearnDuplicate { button: usize, same_as: usize },
+    /// Aprendizado concluído; a tabela foi gravada na configuração.
+    LearnFinished { button_map: ButtonMapConfig },
+    /// Aprendizado cancelado pelo cliente.
+    LearnCancelled,
+    /// Aprendizado falhou.
+    LearnFailed { reason: String },
+    /// A leitura dos endpoints de um dispositivo foi encerrada, com o motivo.
+    ReaderStopped {
+        device: DeviceKey,
+        endpoints: Vec<u8>,
+        exit: ReaderExit,
+    },
+    /// Estatísticas de entrada, em resposta ao comando `Status`.
+    Status(StatsSnapshot),
+    /// Um dispositivo compatível foi detectado, mas a leitura não pôde ser iniciada.
+    SessionFailed { device: DeviceKey, reason: String },
+    /// Resultado da reconexão após a troca do dispositivo configurado;
+    /// `devices` vazio indica que nenhum dispositivo foi conectado.
+    Reattached {
+        vendor_id: u16,
+        product_id: u16,
+        devices: Vec<DeviceKey>,
+    },
+    /// As sessões anteriores não terminaram a tempo após a troca do dispositivo
+    /// configurado; a varredura pelo novo dispositivo não foi feita.
+    ReattachFailed {
+        vendor_id: u16,
+        product_id: u16,
+        reason: String,
+    },
+    /// Sessões ativas, em resposta ao comando `ListSessions`.
+    Sessions { sessions: Vec<SessionInfo> },
+}
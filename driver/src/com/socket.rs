@@ -1,16 +1,23 @@
+use serde::Serialize;
 use std::{
     fs,
     io::{Read, Write},
     os::unix::net::{UnixListener, UnixStream},
     path::Path,
     sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
         mpsc::{self, Receiver, Sender},
         Arc, Mutex,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use crate::translator::translator::EmitCommand;
+
+/// Intervalo mínimo entre eventos de caneta enviados aos clientes (~60 Hz).
+const PEN_FEED_INTERVAL: Duration = Duration::from_millis(16);
+
 /// Servidor de comunicação baseado em Unix Socket.
 ///
 /// Este servidor permite:
@@ -28,7 +35,120 @@ pub struct SocketServer {
     /// Canal para enviar mensagens a todos os clientes.
     tx_broadcast: Sender<Vec<u8>>,
     /// Canal para receber comandos de clientes.
-    rx_commands: Receiver<String>,
+    rx_commands: Mutex<Receiver<String>>,
+    /// Quantidade de clientes conectados no momento.
+    clients: Arc<AtomicUsize>,
+    /// Mensagens que não puderam ser entregues a algum cliente.
+    dropped: Arc<AtomicU64>,
+}
+
+/// Canal de broadcast para os clientes do [`SocketServer`].
+///
+/// Permite consultar se há clientes conectados antes de serializar uma
+/// mensagem, evitando alocações quando ninguém está escutando.
+#[derive(Clone)]
+pub struct Broadcaster {
+    tx: Sender<Vec<u8>>,
+    clients: Arc<AtomicUsize>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Broadcaster {
+    /// Retorna `true` se houver ao menos um cliente conectado.
+    pub fn has_clients(&self) -> bool {
+        self.clients.load(Ordering::Relaxed) > 0
+    }
+
+    /// Envia um pacote binário a todos os clientes conectados.
+    pub fn send(&self, packet: Vec<u8>) {
+        if self.tx.send(packet).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Copia `bytes` em um pacote e envia a todos os clientes conectados.
+    pub fn send_bytes(&self, bytes: &[u8]) {
+        self.send(bytes.to_vec());
+    }
+
+    /// Quantidade de mensagens que não puderam ser entregues a algum cliente
+    /// (canal encerrado ou falha de escrita, que também desconecta o cliente).
+    pub fn dropped_messages(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Serializa `value` como uma linha JSON e envia a todos os clientes.
+    ///
+    /// Não faz nada se não houver clientes conectados.
+    pub fn send_json<T: Serialize>(&self, value: &T) {
+        if !self.has_clients() {
+            return;
+        }
+
+        let mut packet = Vec::with_capacity(96);
+        if serde_json::to_writer(&mut packet, value).is_ok() {
+            packet.push(b'\n');
+            self.send(packet);
+        }
+    }
+}
+
+/// Encaminha aos clientes do socket os comandos produzidos por uma sessão.
+///
+/// Os comandos de cada pacote são serializados como linhas JSON em um buffer
+/// reutilizado e enviados em uma única mensagem. Eventos de caneta são limitados
+/// a um a cada [`PEN_FEED_INTERVAL`] (exceto nas mudanças de contato); os demais
+/// comandos são sempre enviados. Sem clientes conectados, nada é serializado.
+pub struct EmitFeed {
+    tx: Broadcaster,
+    /// Linhas JSON do pacote atual, reutilizado entre pacotes
+    buf: Vec<u8>,
+    /// Momento do último evento de caneta enviado
+    last_pen: Option<Instant>,
+    /// Contato do último evento de caneta enviado
+    touch: bool,
+}
+
+impl EmitFeed {
+    /// Cria o encaminhador de uma sessão.
+    pub fn new(tx: Broadcaster) -> Self {
+        Self {
+            tx,
+            buf: Vec::with_capacity(256),
+            last_pen: None,
+            touch: false,
+        }
+    }
+
+    /// Envia os comandos de um pacote aos clientes conectados.
+    pub fn send(&mut self, commands: &[EmitCommand]) {
+        if !self.tx.has_clients() {
+            return;
+        }
+
+        self.buf.clear();
+        for emit in commands {
+            if let EmitCommand::Pen { touch, .. } = *emit {
+                let now = Instant::now();
+                let due = self
+                    .last_pen
+                    .is_none_or(|last| now.duration_since(last) >= PEN_FEED_INTERVAL);
+                if !due && touch == self.touch {
+                    continue;
+                }
+                self.last_pen = Some(now);
+                self.touch = touch;
+            }
+
+            if serde_json::to_writer(&mut self.buf, emit).is_ok() {
+                self.buf.push(b'\n');
+            }
+        }
+
+        if !self.buf.is_empty() {
+            self.tx.send_bytes(&self.buf);
+        }
+    }
 }
 
 impl SocketServer {
@@ -39,18 +159,23 @@ impl SocketServer {
     /// Retorna um `Arc<SocketServer>` que pode ser compartilhado entre threads.
     pub fn new(path: &str) -> Arc<Self> {
         // Remove o arquivo de socket anterior, se existir
-        if Path::new(path).exists() {
-            if let Err(e) = fs::remove_file(path) {
-                eprintln!("Aviso: não foi possível remover socket antigo: {:?}", e);
-            }
+        if Path::new(path).exists()
+            && let Err(e) = fs::remove_file(path)
+        {
+            eprintln!("Aviso: não foi possível remover socket antigo: {:?}", e);
         }
 
         let (tx_broadcast, rx_broadcast) = mpsc::channel::<Vec<u8>>();
         let (tx_commands, rx_commands) = mpsc::channel::<String>();
 
+        let clients_count = Arc::new(AtomicUsize::new(0));
+        let dropped = Arc::new(AtomicU64::new(0));
+
         let server = Arc::new(Self {
             tx_broadcast,
-            rx_commands,
+            rx_commands: Mutex::new(rx_commands),
+            clients: clients_count.clone(),
+            dropped: dropped.clone(),
         });
 
         let server_ref = Arc::clone(&server);
@@ -74,6 +199,7 @@ impl SocketServer {
             // Thread de aceitação de clientes
             {
                 let clients = Arc::clone(&clients);
+                let clients_count = Arc::clone(&clients_count);
                 let tx_commands = tx_commands.clone();
 
                 thread::spawn(move || {
@@ -87,7 +213,11 @@ impl SocketServer {
                                     continue;
                                 }
 
-                                clients.lock().unwrap().push(stream.try_clone().unwrap());
+                                {
+                                    let mut guard = clients.lock().unwrap();
+                                    guard.push(stream.try_clone().unwrap());
+                                    clients_count.store(guard.len(), Ordering::Relaxed);
+                                }
 
                                 // Thread de leitura para cada cliente
                                 let mut client = match stream.try_clone() {
@@ -144,11 +274,13 @@ impl SocketServer {
                         clients_guard.retain_mut(|client| {
                             if let Err(e) = client.write_all(&packet) {
                                 eprintln!("Erro enviando para cliente: {:?}", e);
+                                dropped.fetch_add(1, Ordering::Relaxed);
                                 false
                             } else {
                                 true
                             }
                         });
+                        clients_count.store(clients_guard.len(), Ordering::Relaxed);
                     }
                     Err(_) => {
                         eprintln!("Canal de broadcast encerrado, servidor terminando.");
@@ -161,14 +293,18 @@ impl SocketServer {
         server_ref
     }
 
-    /// Retorna um `Sender` para enviar mensagens a todos os clientes conectados.
-    pub fn sender(&self) -> Sender<Vec<u8>> {
-        self.tx_broadcast.clone()
+    /// Retorna um [`Broadcaster`] para enviar mensagens a todos os clientes conectados.
+    pub fn sender(&self) -> Broadcaster {
+        Broadcaster {
+            tx: self.tx_broadcast.clone(),
+            clients: self.clients.clone(),
+            dropped: self.dropped.clone(),
+        }
     }
 
     /// Tenta receber um comando enviado por algum cliente.
     /// Retorna `None` se não houver mensagens disponíveis.
     pub fn try_recv_command(&self) -> Option<String> {
-        self.rx_commands.try_recv().ok()
+        self.rx_commands.lock().unwrap().try_recv().ok()
     }
 }
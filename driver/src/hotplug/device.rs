@@ -0,0 +1,272 @@
+use rusb::{Context, Device};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::hotplug::CustomHotplugEvent;
+use crate::session::DeviceKey;
+
+/// Identifica um dispositivo USB físico: barramento, endereço e caminho de portas.
+///
+/// O endereço é reatribuído pelo kernel a cada conexão, e pode ser reutilizado
+/// por outro dispositivo; o caminho de portas (`1-2.3` → `[2, 3]`) distingue
+/// esses casos.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct UsbDeviceId {
+    pub bus: u8,
+    pub address: u8,
+    pub port_path: Vec<u8>,
+}
+
+impl UsbDeviceId {
+    /// Lê a identificação de um dispositivo da libusb.
+    ///
+    /// Também funciona em `DeviceLeft`, pois a libusb mantém os dados do dispositivo.
+    pub fn from_device(device: &Device<Context>) -> Self {
+        Self {
+            bus: device.bus_number(),
+            address: device.address(),
+            port_path: device.port_numbers().unwrap_or_default(),
+        }
+    }
+
+    /// Chave da sessão associada ao dispositivo.
+    pub fn key(&self) -> DeviceKey {
+        DeviceKey {
+            bus: self.bus,
+            address: self.address,
+        }
+    }
+}
+
+impl fmt::Display for UsbDeviceId {
+    /// Formato do sysfs (`1-2.3`), seguido do endereço.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ports: Vec<String> = self.port_path.iter().map(|p| p.to_string()).collect();
+        write!(f, "{}-{} (endereço {})", self.bus, ports.join("."), self.address)
+    }
+}
+
+/// Dados de um dispositivo recebidos em um evento de hotplug.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub id: UsbDeviceId,
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
+
+impl DeviceInfo {
+    /// Lê identificação e descritor de um dispositivo da libusb.
+    pub fn from_device(device: &Device<Context>) -> rusb::Result<Self> {
+        let desc = device.device_descriptor()?;
+        Ok(Self {
+            id: UsbDeviceId::from_device(device),
+            vendor_id: desc.vendor_id(),
+            product_id: desc.product_id(),
+        })
+    }
+}
+
+/// Evento de hotplug relevante para o driver, após a filtragem do [`DeviceWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    /// Um dispositivo compatível foi conectado.
+    Attached(UsbDeviceId),
+    /// Um dispositivo compatível acompanhado foi removido.
+    Detached(UsbDeviceId),
+}
+
+/// Filtra os eventos de hotplug, acompanhando os dispositivos compatíveis conectados.
+///
+/// Apenas dispositivos com o `vendor_id`/`product_id` configurados geram
+/// [`WatchEvent::Attached`], e apenas a remoção de um desses dispositivos
+/// (mesmo barramento, endereço e caminho de portas) gera
+/// [`WatchEvent::Detached`]: desconectar um pendrive não afeta a mesa.
+#[derive(Debug)]
+pub struct DeviceWatcher {
+    vendor_id: u16,
+    product_id: u16,
+    attached: Vec<UsbDeviceId>,
+}
+
+impl DeviceWatcher {
+    /// Cria um filtro para o dispositivo `vendor_id:product_id`.
+    pub fn new(vendor_id: u16, product_id: u16) -> Self {
+        Self {
+            vendor_id,
+            product_id,
+            attached: Vec::new(),
+        }
+    }
+
+    /// Passa a considerar compatível o dispositivo `vendor_id:product_id`.
+    ///
+    /// Os dispositivos já acompanhados continuam sendo até a sua remoção.
+    pub fn set_target(&mut self, vendor_id: u16, product_id: u16) {
+        self.vendor_id = vendor_id;
+        self.product_id = product_id;
+    }
+
+    /// Processa um evento de hotplug, retornando o evento relevante, se houver.
+    ///
+    /// Conexões repetidas de um dispositivo já acompanhado (ex: enumeração
+    /// inicial) são ignoradas.
+    pub fn handle(&mut self, device: &DeviceInfo, event: CustomHotplugEvent) -> Option<WatchEvent> {
+        match event {
+            CustomHotplugEvent::DeviceArrived => {
+                if device.vendor_id != self.vendor_id
+                    || device.product_id != self.product_id
+                    || self.attached.contains(&device.id)
+                {
+                    return None;
+                }
+                self.attached.push(device.id.clone());
+                Some(WatchEvent::Attached(device.id.clone()))
+            }
+            CustomHotplugEvent::DeviceLeft => {
+                let index = self.attached.iter().position(|id| *id == device.id)?;
+                Some(WatchEvent::Detached(self.attached.remove(index)))
+            }
+        }
+    }
+
+    /// Dispositivos compatíveis conectados.
+    pub fn attached(&self) -> &[UsbDeviceId] {
+        &self.attached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VID: u16 = 0x08f2;
+    const PID: u16 = 0x6811;
+
+    /// Origem de hotplug simulada: entrega eventos ao [`DeviceWatcher`] como o
+    /// callback de `main.rs` e registra as sessões iniciadas e encerradas.
+    struct MockHotplug {
+        watcher: DeviceWatcher,
+        started: Vec<UsbDeviceId>,
+        stopped: Vec<UsbDeviceId>,
+    }
+
+    impl MockHotplug {
+        fn new() -> Self {
+            Self {
+                watcher: DeviceWatcher::new(VID, PID),
+                started: Vec::new(),
+                stopped: Vec::new(),
+            }
+        }
+
+        fn emit(&mut self, device: &DeviceInfo, event: CustomHotplugEvent) {
+            match self.watcher.handle(device, event) {
+                Some(WatchEvent::Attached(id)) => self.started.push(id),
+                Some(WatchEvent::Detached(id)) => self.stopped.push(id),
+                None => {}
+            }
+        }
+
+        fn arrive(&mut self, device: &DeviceInfo) {
+            self.emit(device, CustomHotplugEvent::DeviceArrived);
+        }
+
+        fn leave(&mut self, device: &DeviceInfo) {
+            self.emit(device, CustomHotplugEvent::DeviceLeft);
+        }
+    }
+
+    fn device(vendor_id: u16, product_id: u16, address: u8, port_path: &[u8]) -> DeviceInfo {
+        DeviceInfo {
+            id: UsbDeviceId {
+                bus: 1,
+                address,
+                port_path: port_path.to_vec(),
+            },
+            vendor_id,
+            product_id,
+        }
+    }
+
+    #[test]
+    fn unrelated_device_leaving_keeps_tablet() {
+        let tablet = device(VID, PID, 5, &[2]);
+        let flash_drive = device(0x0781, 0x5567, 6, &[3]);
+        let mut hotplug = MockHotplug::new();
+
+        hotplug.arrive(&tablet);
+        hotplug.arrive(&flash_drive);
+        hotplug.leave(&flash_drive);
+
+        assert_eq!(hotplug.started, vec![tablet.id.clone()]);
+        assert!(hotplug.stopped.is_empty());
+        assert_eq!(hotplug.watcher.attached(), &[tablet.id]);
+    }
+
+    #[test]
+    fn tablet_leaving_stops_its_session() {
+        let tablet = device(VID, PID, 5, &[2]);
+        let mut hotplug = MockHotplug::new();
+
+        hotplug.arrive(&tablet);
+        hotplug.leave(&tablet);
+
+        assert_eq!(hotplug.stopped, vec![tablet.id]);
+        assert!(hotplug.watcher.attached().is_empty());
+    }
+
+    #[test]
+    fn only_the_matching_tablet_is_stopped() {
+        let first = device(VID, PID, 5, &[2]);
+        let second = device(VID, PID, 8, &[4, 1]);
+        let mut hotplug = MockHotplug::new();
+
+        hotplug.arrive(&first);
+        hotplug.arrive(&second);
+        hotplug.leave(&second);
+
+        assert_eq!(hotplug.started, vec![first.id.clone(), second.id.clone()]);
+        assert_eq!(hotplug.stopped, vec![second.id]);
+        assert_eq!(hotplug.watcher.attached(), &[first.id]);
+    }
+
+    #[test]
+    fn reused_address_on_another_port_is_ignored() {
+        let tablet = device(VID, PID, 5, &[2]);
+        let other = device(0x0781, 0x5567, 5, &[3]);
+        let mut hotplug = MockHotplug::new();
+
+        hotplug.arrive(&tablet);
+        hotplug.leave(&other);
+
+        assert!(hotplug.stopped.is_empty());
+    }
+
+    #[test]
+    fn repeated_arrival_starts_once() {
+        let tablet = device(VID, PID, 5, &[2]);
+        let mut hotplug = MockHotplug::new();
+
+        hotplug.arrive(&tablet);
+        hotplug.arrive(&tablet);
+        hotplug.leave(&tablet);
+        hotplug.leave(&tablet);
+
+        assert_eq!(hotplug.started.len(), 1);
+        assert_eq!(hotplug.stopped.len(), 1);
+    }
+
+    #[test]
+    fn replug_starts_a_new_session() {
+        let before = device(VID, PID, 5, &[2]);
+        let after = device(VID, PID, 9, &[2]);
+        let mut hotplug = MockHotplug::new();
+
+        hotplug.arrive(&before);
+        hotplug.leave(&before);
+        hotplug.arrive(&after);
+
+        assert_eq!(hotplug.started, vec![before.id.clone(), after.id]);
+        assert_eq!(hotplug.stopped, vec![before.id]);
+    }
+}
@@ -1,7 +1,15 @@
 use rusb::{Context, Device, Hotplug, HotplugBuilder, UsbContext};
-use std::sync::{Arc, Mutex};
+use std::collections::BTreeMap;
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use table_z_config::{HotplugBackend, HotplugConfig};
+
+use super::device::UsbDeviceId;
+use super::uevent::{UeventFilter, UeventSocket};
 
 /// Evento personalizado de hotplug USB.
 ///
@@ -14,6 +22,9 @@ pub enum CustomHotplugEvent {
     DeviceLeft,
 }
 
+/// Callback compartilhado chamado a cada evento de hotplug.
+type HotplugCallback = Arc<Mutex<dyn FnMut(Device<Context>, CustomHotplugEvent) + Send>>;
+
 /// Estrutura que trata eventos de hotplug.
 ///
 /// Internamente, mantém um callback compartilhado e protegido por `Mutex`
@@ -22,7 +33,7 @@ pub struct HotPlugHandler {
     /// Função callback chamada em cada evento USB.
     ///
     /// O callback recebe o dispositivo e o tipo de evento (`arrived` ou `left`).
-    callback: Arc<Mutex<dyn FnMut(Device<Context>, CustomHotplugEvent) + Send>>,
+    callback: HotplugCallback,
 }
 
 impl Hotplug<Context> for HotPlugHandler {
@@ -37,6 +48,60 @@ impl Hotplug<Context> for HotPlugHandler {
     }
 }
 
+/// Controle do monitoramento iniciado por [`HotPlugHandler::init`].
+///
+/// Permite trocar o dispositivo procurado e solicitar uma nova varredura do
+/// barramento, sem reiniciar o driver.
+#[derive(Clone)]
+pub struct HotplugControl {
+    /// Dispositivo procurado (usado pelo backend `udev`)
+    target: Arc<Mutex<UeventFilter>>,
+    /// Varredura solicitada e ainda não concluída
+    rescan: Arc<AtomicBool>,
+}
+
+impl HotplugControl {
+    fn new(vendor_id: u16, product_id: u16) -> Self {
+        Self {
+            target: Arc::new(Mutex::new(UeventFilter {
+                vendor_id,
+                product_id,
+            })),
+            rescan: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Passa a procurar `vendor_id:product_id` e solicita uma nova varredura.
+    ///
+    /// A varredura sinaliza `DeviceLeft` e em seguida `DeviceArrived` para cada
+    /// dispositivo conectado, para que o callback reavalie todos eles.
+    pub fn rescan(&self, vendor_id: u16, product_id: u16) {
+        *self.target.lock().unwrap() = UeventFilter {
+            vendor_id,
+            product_id,
+        };
+        self.rescan.store(true, Ordering::SeqCst);
+    }
+
+    /// Retorna `true` enquanto a varredura solicitada não tiver terminado.
+    pub fn rescan_pending(&self) -> bool {
+        self.rescan.load(Ordering::SeqCst)
+    }
+
+    /// Dispositivo procurado.
+    fn filter(&self) -> UeventFilter {
+        *self.target.lock().unwrap()
+    }
+
+    /// Executa `rescan` se houver uma varredura solicitada.
+    fn run_pending_rescan(&self, rescan: impl FnOnce()) {
+        if self.rescan_pending() {
+            rescan();
+            self.rescan.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
 impl HotPlugHandler {
     /// Inicializa o monitoramento de eventos USB (hotplug).
     ///
@@ -44,28 +109,37 @@ impl HotPlugHandler {
     /// e desconexão de dispositivos USB, chamando o `callback` fornecido sempre
     /// que um evento é detectado.
     ///
+    /// Com o backend `udev`, os eventos vêm do netlink, já processados pelo udev,
+    /// filtrados por `vendor_id`/`product_id`. Com o backend `poll`, ou se o
+    /// backend escolhido não estiver disponível, os eventos são sintetizados a
+    /// partir de enumerações periódicas (`poll_interval_ms`).
+    ///
+    /// O mesmo loop de eventos conclui as transferências assíncronas dos
+    /// leitores abertos em dispositivos deste contexto (ver `USBReader`).
+    ///
+    /// Retorna um [`HotplugControl`] para trocar o dispositivo procurado.
+    ///
     /// # Exemplo
-    /// ```
-    /// HotPlugHandler::init(|device, event| {
+    /// ```ignore
+    /// let control = HotPlugHandler::init(&HotplugConfig::default(), 0x08f2, 0x6811, |device, event| {
     ///     match event {
     ///         CustomHotplugEvent::DeviceArrived => println!("Novo dispositivo: {:?}", device),
     ///         CustomHotplugEvent::DeviceLeft => println!("Dispositivo removido: {:?}", device),
     ///     }
     /// });
     /// ```
-    pub fn init<F>(callback: F)
+    pub fn init<F>(config: &HotplugConfig, vendor_id: u16, product_id: u16, callback: F) -> HotplugControl
     where
         F: FnMut(Device<Context>, CustomHotplugEvent) + Send + 'static,
     {
-        // Verifica se o suporte a hotplug está disponível na versão da libusb.
-        if !rusb::has_hotplug() {
-            eprintln!("⚠️ libusb hotplug não suportado nesta versão.");
-            return;
-        }
-
-        let cb = Arc::new(Mutex::new(callback));
+        let cb: HotplugCallback = Arc::new(Mutex::new(callback));
+        let control = HotplugControl::new(vendor_id, product_id);
+        let backend = config.backend;
+        let poll_interval = Duration::from_millis(config.poll_interval_ms).max(MIN_POLL_INTERVAL);
 
+        let thread_control = control.clone();
         thread::spawn(move || {
+            let control = thread_control;
             let context = match Context::new() {
                 Ok(ctx) => ctx,
                 Err(e) => {
@@ -74,31 +148,239 @@ impl HotPlugHandler {
                 }
             };
 
-            // Registra o handler de hotplug
-            let _registration = match HotplugBuilder::new()
-                .enumerate(true)
-                .register(
-                    &context,
-                    Box::new(HotPlugHandler {
-                        callback: cb.clone(),
-                    }),
-                ) {
-                Ok(reg) => reg,
-                Err(e) => {
-                    eprintln!("❌ Falha ao registrar callback hotplug: {:?}", e);
-                    return;
+            // Registra o handler de hotplug, se disponível
+            let registration = match backend {
+                HotplugBackend::Libusb if rusb::has_hotplug() => {
+                    match HotplugBuilder::new().enumerate(true).register(
+                        &context,
+                        Box::new(HotPlugHandler {
+                            callback: cb.clone(),
+                        }),
+                    ) {
+                        Ok(reg) => Some(reg),
+                        Err(e) => {
+                            eprintln!("⚠️ Falha ao registrar callback hotplug: {:?}", e);
+                            None
+                        }
+                    }
                 }
+                HotplugBackend::Libusb => {
+                    eprintln!("⚠️ libusb hotplug não suportado nesta versão.");
+                    None
+                }
+                HotplugBackend::Udev | HotplugBackend::Poll => None,
             };
 
-            println!("🔌 [Hotplug] Monitorando eventos USB...");
+            match registration {
+                Some(_registration) => {
+                    println!("🔌 [Hotplug] Monitorando eventos USB...");
 
-            // Loop de eventos principal
-            loop {
-                if let Err(e) = context.handle_events(Some(Duration::from_millis(200))) {
-                    eprintln!("⚠️ Erro no handle_events: {:?}", e);
-                    thread::sleep(Duration::from_secs(1)); // pequena pausa antes de tentar novamente
+                    // Loop de eventos principal (hotplug e transferências USB)
+                    loop {
+                        handle_events(&context, EVENT_TIMEOUT);
+                        control.run_pending_rescan(|| {
+                            if let Ok(list) = context.devices() {
+                                replay(&cb, list.iter().collect());
+                            }
+                        });
+                    }
                 }
+                None if backend == HotplugBackend::Udev => match UeventSocket::open() {
+                    Ok(socket) => {
+                        println!("🔌 [Hotplug] Monitorando eventos do udev...");
+                        watch_uevents(&context, &cb, &socket, &control);
+                    }
+                    Err(e) => {
+                        eprintln!("⚠️ Falha ao abrir socket netlink de uevents: {e}");
+                        poll_devices(&context, &cb, poll_interval, &control);
+                    }
+                },
+                None => poll_devices(&context, &cb, poll_interval, &control),
             }
         });
+
+        control
+    }
+}
+
+/// Intervalo mínimo entre enumerações do backend `poll`.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Tamanho do buffer de leitura de *uevents*.
+const UEVENT_BUFFER_SIZE: usize = 8192;
+
+/// Tempo máximo de espera de cada chamada a `handle_events`.
+const EVENT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Processa os eventos pendentes da libusb (hotplug e transferências USB).
+fn handle_events(context: &Context, timeout: Duration) {
+    if let Err(e) = context.handle_events(Some(timeout)) {
+        eprintln!("⚠️ Erro no handle_events: {:?}", e);
+        thread::sleep(Duration::from_secs(1)); // pequena pausa antes de tentar novamente
+    }
+}
+
+/// Sinaliza a remoção e em seguida a conexão de cada dispositivo, para que o
+/// callback reavalie quais deles devem ser lidos.
+fn replay(callback: &HotplugCallback, devices: Vec<Device<Context>>) {
+    for device in &devices {
+        (callback.lock().unwrap())(device.clone(), CustomHotplugEvent::DeviceLeft);
+    }
+    for device in devices {
+        (callback.lock().unwrap())(device, CustomHotplugEvent::DeviceArrived);
+    }
+}
+
+/// Dispositivos conectados, indexados pela identificação física.
+type DeviceList = BTreeMap<UsbDeviceId, Device<Context>>;
+
+/// Compara duas enumerações, retornando os dispositivos conectados e removidos.
+fn diff_devices(previous: &DeviceList, current: &DeviceList) -> (Vec<Device<Context>>, Vec<Device<Context>>) {
+    let arrived = current
+        .iter()
+        .filter(|(id, _)| !previous.contains_key(id))
+        .map(|(_, device)| device.clone())
+        .collect();
+    let left = previous
+        .iter()
+        .filter(|(id, _)| !current.contains_key(id))
+        .map(|(_, device)| device.clone())
+        .collect();
+    (arrived, left)
+}
+
+/// Sintetiza eventos de hotplug enumerando os dispositivos a cada `interval`.
+///
+/// Entre as enumerações, continua processando os eventos da libusb, dos quais
+/// dependem as transferências assíncronas dos leitores.
+fn poll_devices(context: &Context, callback: &HotplugCallback, interval: Duration, control: &HotplugControl) {
+    println!(
+        "🔌 [Hotplug] Monitorando dispositivos USB por enumeração a cada {} ms...",
+        interval.as_millis()
+    );
+
+    let mut known = DeviceList::new();
+    let mut last_poll: Option<Instant> = None;
+
+    loop {
+        if last_poll.is_none_or(|t| t.elapsed() >= interval) {
+            last_poll = Some(Instant::now());
+
+            match context.devices() {
+                Ok(list) => {
+                    let current: DeviceList = list
+                        .iter()
+                        .map(|device| (UsbDeviceId::from_device(&device), device))
+                        .collect();
+                    let (arrived, left) = diff_devices(&known, &current);
+
+                    for device in left {
+                        (callback.lock().unwrap())(device, CustomHotplugEvent::DeviceLeft);
+                    }
+                    for device in arrived {
+                        (callback.lock().unwrap())(device, CustomHotplugEvent::DeviceArrived);
+                    }
+                    known = current;
+                }
+                Err(e) => eprintln!("⚠️ Erro ao enumerar dispositivos USB: {:?}", e),
+            }
+        }
+
+        control.run_pending_rescan(|| replay(callback, known.values().cloned().collect()));
+
+        handle_events(context, EVENT_TIMEOUT.min(interval));
+    }
+}
+
+/// Tentativas de localizar na libusb um dispositivo anunciado pelo udev.
+const LOOKUP_ATTEMPTS: u32 = 10;
+
+/// Localiza o dispositivo da libusb com a identificação `id`.
+///
+/// A lista da libusb pode ser atualizada alguns instantes depois do evento do
+/// udev; a busca é repetida enquanto os eventos da libusb são processados.
+fn find_device(context: &Context, id: &UsbDeviceId) -> Option<Device<Context>> {
+    for _ in 0..LOOKUP_ATTEMPTS {
+        if let Ok(list) = context.devices()
+            && let Some(device) = list.iter().find(|d| UsbDeviceId::from_device(d) == *id)
+        {
+            return Some(device);
+        }
+        handle_events(context, Duration::from_millis(50));
+    }
+    None
+}
+
+/// Sinaliza a conexão dos dispositivos já conectados que passam pelo filtro.
+fn announce_connected(context: &Context, callback: &HotplugCallback, filter: UeventFilter, known: &mut DeviceList) {
+    let Ok(list) = context.devices() else {
+        return;
+    };
+    for device in list.iter() {
+        let Ok(desc) = device.device_descriptor() else {
+            continue;
+        };
+        if desc.vendor_id() == filter.vendor_id && desc.product_id() == filter.product_id {
+            known.insert(UsbDeviceId::from_device(&device), device.clone());
+            (callback.lock().unwrap())(device, CustomHotplugEvent::DeviceArrived);
+        }
+    }
+}
+
+/// Gera eventos de hotplug a partir dos *uevents* processados pelo udev.
+///
+/// Os dispositivos já conectados são anunciados na inicialização. Entre as
+/// mensagens, continua processando os eventos da libusb, dos quais dependem
+/// as transferências assíncronas dos leitores.
+fn watch_uevents(context: &Context, callback: &HotplugCallback, socket: &UeventSocket, control: &HotplugControl) {
+    let mut known = DeviceList::new();
+    announce_connected(context, callback, control.filter(), &mut known);
+
+    let mut buf = vec![0u8; UEVENT_BUFFER_SIZE];
+    loop {
+        // Nova varredura: remove os dispositivos anunciados e procura o novo alvo
+        control.run_pending_rescan(|| {
+            for (_, device) in std::mem::take(&mut known) {
+                (callback.lock().unwrap())(device, CustomHotplugEvent::DeviceLeft);
+            }
+            announce_connected(context, callback, control.filter(), &mut known);
+        });
+
+        let filter = control.filter();
+        loop {
+            let len = match socket.recv(&mut buf) {
+                Ok(Some(len)) => len,
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("⚠️ Erro lendo uevent: {e}");
+                    break;
+                }
+            };
+            let Some((event, info)) = filter.filter(&buf[..len]) else {
+                continue;
+            };
+
+            match event {
+                CustomHotplugEvent::DeviceArrived => {
+                    if known.contains_key(&info.id) {
+                        continue;
+                    }
+                    match find_device(context, &info.id) {
+                        Some(device) => {
+                            known.insert(info.id, device.clone());
+                            (callback.lock().unwrap())(device, CustomHotplugEvent::DeviceArrived);
+                        }
+                        None => eprintln!("⚠️ Dispositivo {} anunciado pelo udev não encontrado na libusb.", info.id),
+                    }
+                }
+                CustomHotplugEvent::DeviceLeft => {
+                    if let Some(device) = known.remove(&info.id) {
+                        (callback.lock().unwrap())(device, CustomHotplugEvent::DeviceLeft);
+                    }
+                }
+            }
+        }
+
+        handle_events(context, EVENT_TIMEOUT);
     }
 }
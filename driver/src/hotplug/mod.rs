@@ -1,2 +1,6 @@
+pub mod device;
 pub mod hotplug;
-pub use hotplug::HotPlugHandler;
\ No newline at end of file
+pub mod uevent;
+pub use device::{DeviceInfo, DeviceWatcher, UsbDeviceId, WatchEvent};
+pub use hotplug::{HotPlugHandler, HotplugControl};
+pub use uevent::{Uevent, UeventFilter, UeventSocket};
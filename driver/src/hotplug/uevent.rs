@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use super::device::{DeviceInfo, UsbDeviceId};
+use super::hotplug::CustomHotplugEvent;
+
+/// Grupo multicast netlink dos eventos enviados diretamente pelo kernel.
+const KERNEL_MONITOR_GROUP: u32 = 1;
+
+/// Grupo multicast netlink dos eventos já processados pelo udev.
+const UDEV_MONITOR_GROUP: u32 = 2;
+
+/// Prefixo das mensagens enviadas pelo udev.
+const UDEV_PREFIX: &[u8] = b"libudev\0";
+
+/// Valor mágico do cabeçalho das mensagens do udev (*big-endian*).
+const UDEV_MAGIC: u32 = 0xfeed_cafe;
+
+/// Tamanho mínimo do cabeçalho das mensagens do udev.
+const UDEV_HEADER_SIZE: usize = 40;
+
+/// Evento de dispositivo recebido do kernel ou do udev (*uevent*).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Uevent {
+    /// `true` se a mensagem veio do udev (regras já aplicadas)
+    pub from_udev: bool,
+    /// Propriedades `CHAVE=valor` do evento
+    pub properties: HashMap<String, String>,
+}
+
+impl Uevent {
+    /// Interpreta uma mensagem netlink do kernel (`add@/devices/...`) ou do udev
+    /// (cabeçalho `libudev`).
+    ///
+    /// Retorna `None` se a mensagem estiver malformada.
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        let (from_udev, body) = if buf.starts_with(UDEV_PREFIX) {
+            if buf.len() < UDEV_HEADER_SIZE {
+                return None;
+            }
+            let word = |offset: usize| buf[offset..offset + 4].try_into().ok();
+            if u32::from_be_bytes(word(8)?) != UDEV_MAGIC {
+                return None;
+            }
+            let offset = u32::from_ne_bytes(word(16)?) as usize;
+            let len = u32::from_ne_bytes(word(20)?) as usize;
+            (true, buf.get(offset..offset.checked_add(len)?)?)
+        } else {
+            // Mensagem do kernel: o primeiro campo é `ação@devpath`
+            let header_end = buf.iter().position(|&b| b == 0)?;
+            if !buf[..header_end].contains(&b'@') {
+                return None;
+            }
+            (false, &buf[header_end + 1..])
+        };
+
+        let properties = body
+            .split(|&b| b == 0)
+            .filter_map(|field| {
+                let field = std::str::from_utf8(field).ok()?;
+                let (key, value) = field.split_once('=')?;
+                Some((key.to_string(), value.to_string()))
+            })
+            .collect();
+
+        Some(Self {
+            from_udev,
+            properties,
+        })
+    }
+
+    /// Valor de uma propriedade.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+
+    /// Converte em um evento de hotplug de dispositivo USB.
+    ///
+    /// Considera apenas `add`/`remove` de dispositivos USB inteiros
+    /// (`SUBSYSTEM=usb`, `DEVTYPE=usb_device`), não de suas interfaces.
+    pub fn usb_device_event(&self) -> Option<(CustomHotplugEvent, DeviceInfo)> {
+        if self.get("SUBSYSTEM")? != "usb" || self.get("DEVTYPE")? != "usb_device" {
+            return None;
+        }
+        let event = match self.get("ACTION")? {
+            "add" => CustomHotplugEvent::DeviceArrived,
+            "remove" => CustomHotplugEvent::DeviceLeft,
+            _ => return None,
+        };
+
+        // PRODUCT=vid/pid/bcdDevice, em hexadecimal
+        let mut product = self.get("PRODUCT")?.split('/');
+        let vendor_id = u16::from_str_radix(product.next()?, 16).ok()?;
+        let product_id = u16::from_str_radix(product.next()?, 16).ok()?;
+
+        Some((
+            event,
+            DeviceInfo {
+                id: UsbDeviceId {
+                    bus: self.get("BUSNUM")?.parse().ok()?,
+                    address: self.get("DEVNUM")?.parse().ok()?,
+                    port_path: port_path(self.get("DEVPATH")?)?,
+                },
+                vendor_id,
+                product_id,
+            },
+        ))
+    }
+}
+
+/// Extrai o caminho de portas do `DEVPATH` (`.../usb1/1-2/1-2.3` → `[2, 3]`).
+///
+/// Hubs raiz (`.../usb1`) não têm portas.
+fn port_path(devpath: &str) -> Option<Vec<u8>> {
+    let name = devpath.rsplit('/').next()?;
+    if name.starts_with("usb") {
+        return Some(Vec::new());
+    }
+    let (_bus, ports) = name.split_once('-')?;
+    ports.split('.').map(|p| p.parse().ok()).collect()
+}
+
+/// Filtra os *uevents* de um dispositivo `vendor_id:product_id`.
+#[derive(Debug, Clone, Copy)]
+pub struct UeventFilter {
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
+
+impl UeventFilter {
+    /// Interpreta uma mensagem netlink, retornando o evento de hotplug se ela
+    /// se referir ao dispositivo filtrado.
+    ///
+    /// Conexões só são sinalizadas por mensagens do udev, enviadas depois que
+    /// as regras (permissões, grupos) foram aplicadas ao nó do dispositivo.
+    pub fn filter(&self, buf: &[u8]) -> Option<(CustomHotplugEvent, DeviceInfo)> {
+        let uevent = Uevent::parse(buf)?;
+        let (event, device) = uevent.usb_device_event()?;
+
+        if device.vendor_id != self.vendor_id || device.product_id != self.product_id {
+            return None;
+        }
+        if matches!(event, CustomHotplugEvent::DeviceArrived) && !uevent.from_udev {
+            return None;
+        }
+        Some((event, device))
+    }
+}
+
+/// Socket netlink (não bloqueante) inscrito nos eventos do kernel e do udev.
+///
+/// As remoções chegam primeiro pelo grupo do kernel; as conexões só são
+/// consideradas quando anunciadas pelo udev (ver [`UeventFilter::filter`]).
+pub struct UeventSocket {
+    fd: OwnedFd,
+}
+
+impl UeventSocket {
+    /// Abre o socket, se inscreve nos grupos de eventos do kernel e do udev e
+    /// habilita o recebimento das credenciais do remetente (`SO_PASSCRED`).
+    pub fn open() -> io::Result<Self> {
+        // SAFETY: chamadas diretas à API de sockets; o descritor retornado é
+        // verificado e passa a pertencer ao `OwnedFd`.
+        unsafe {
+            let fd = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+                libc::NETLINK_KOBJECT_UEVENT,
+            );
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let fd = OwnedFd::from_raw_fd(fd);
+
+            let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            addr.nl_groups = KERNEL_MONITOR_GROUP | UDEV_MONITOR_GROUP;
+
+            let ret = libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            );
+            if ret < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let on: libc::c_int = 1;
+            let ret = libc::setsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PASSCRED,
+                &on as *const libc::c_int as *const libc::c_void,
+                std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+            );
+            if ret < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(Self { fd })
+        }
+    }
+
+    /// Lê uma mensagem pendente, retornando seu tamanho, ou `None` se não houver.
+    ///
+    /// Como no libudev, descarta mensagens truncadas e as que não vieram do
+    /// kernel nem do udev: sem credenciais de `root` (`SCM_CREDENTIALS`), enviadas
+    /// diretamente ao socket (*unicast*) ou, no grupo do kernel, com `nl_pid != 0`.
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        loop {
+            // SAFETY: apenas calcula o espaço de uma mensagem de controle.
+            let cred_space = unsafe { libc::CMSG_SPACE(std::mem::size_of::<libc::ucred>() as u32) };
+            let mut control = [0u64; 8];
+            debug_assert!(cred_space as usize <= std::mem::size_of_val(&control));
+
+            // SAFETY: estruturas C sem invariantes; zeradas são válidas.
+            let mut sender: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+            let mut iov = libc::iovec {
+                iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+                iov_len: buf.len(),
+            };
+            let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+            msg.msg_name = &mut sender as *mut libc::sockaddr_nl as *mut libc::c_void;
+            msg.msg_namelen = std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+            msg.msg_controllen = cred_space as _;
+
+            // SAFETY: `msg` aponta para `buf`, `sender` e `control`, válidos
+            // para escrita nos tamanhos informados.
+            let n = unsafe { libc::recvmsg(self.fd.as_raw_fd(), &mut msg, 0) };
+            if n < 0 {
+                let err = io::Error::last_os_error();
+                return match err.kind() {
+                    io::ErrorKind::WouldBlock => Ok(None),
+                    io::ErrorKind::Interrupted => Ok(None),
+                    _ => Err(err),
+                };
+            }
+
+            // SAFETY: `msg` foi preenchido pelo `recvmsg` acima.
+            let trusted = unsafe { Self::trusted_sender(&msg, &sender) };
+            if trusted && msg.msg_flags & libc::MSG_TRUNC == 0 {
+                return Ok(Some(n as usize));
+            }
+        }
+    }
+
+    /// Verifica o remetente de uma mensagem recebida por `recvmsg`.
+    ///
+    /// # Safety
+    /// `msg` deve ter sido preenchido por `recvmsg`, com `msg_control` ainda válido.
+    unsafe fn trusted_sender(msg: &libc::msghdr, sender: &libc::sockaddr_nl) -> bool {
+        match sender.nl_groups {
+            0 => return false,
+            KERNEL_MONITOR_GROUP if sender.nl_pid != 0 => return false,
+            _ => {}
+        }
+
+        // SAFETY: garantido pelo chamador.
+        unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(msg);
+            if cmsg.is_null()
+                || (*cmsg).cmsg_level != libc::SOL_SOCKET
+                || (*cmsg).cmsg_type != libc::SCM_CREDENTIALS
+            {
+                return false;
+            }
+            let cred = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::ucred);
+            cred.uid == 0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLET: UeventFilter = UeventFilter {
+        vendor_id: 0x08f2,
+        product_id: 0x6811,
+    };
+
+    fn properties(action: &str, product: &str, devpath: &str, devtype: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+        for field in [
+            format!("ACTION={action}"),
+            format!("DEVPATH={devpath}"),
+            "SUBSYSTEM=usb".to_string(),
+            format!("DEVTYPE={devtype}"),
+            format!("PRODUCT={product}"),
+            "BUSNUM=001".to_string(),
+            "DEVNUM=007".to_string(),
+        ] {
+            body.extend_from_slice(field.as_bytes());
+            body.push(0);
+        }
+        body
+    }
+
+    /// Mensagem no formato do kernel (`KERNEL_MONITOR_GROUP`).
+    fn kernel_message(action: &str, product: &str, devpath: &str) -> Vec<u8> {
+        let mut buf = format!("{action}@{devpath}\0").into_bytes();
+        buf.extend(properties(action, product, devpath, "usb_device"));
+        buf
+    }
+
+    /// Mensagem no formato do udev (`UDEV_MONITOR_GROUP`).
+    fn udev_message(action: &str, product: &str, devpath: &str, devtype: &str) -> Vec<u8> {
+        let body = properties(action, product, devpath, devtype);
+        let mut buf = UDEV_PREFIX.to_vec();
+        buf.extend(UDEV_MAGIC.to_be_bytes());
+        buf.extend((UDEV_HEADER_SIZE as u32).to_ne_bytes());
+        buf.extend((UDEV_HEADER_SIZE as u32).to_ne_bytes());
+        buf.extend((body.len() as u32).to_ne_bytes());
+        buf.resize(UDEV_HEADER_SIZE, 0);
+        buf.extend(body);
+        buf
+    }
+
+    const DEVPATH: &str = "/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2.3";
+
+    #[test]
+    fn udev_add_signals_arrival() {
+        let msg = udev_message("add", "8f2/6811/100", DEVPATH, "usb_device");
+        let (event, device) = TABLET.filter(&msg).unwrap();
+
+        assert!(matches!(event, CustomHotplugEvent::DeviceArrived));
+        assert_eq!(
+            device.id,
+            UsbDeviceId {
+                bus: 1,
+                address: 7,
+                port_path: vec![2, 3],
+            }
+        );
+    }
+
+    #[test]
+    fn kernel_add_waits_for_udev() {
+        let msg = kernel_message("add", "8f2/6811/100", DEVPATH);
+        assert!(TABLET.filter(&msg).is_none());
+    }
+
+    #[test]
+    fn kernel_remove_signals_departure() {
+        let msg = kernel_message("remove", "8f2/6811/100", DEVPATH);
+        let (event, _) = TABLET.filter(&msg).unwrap();
+        assert!(matches!(event, CustomHotplugEvent::DeviceLeft));
+    }
+
+    #[test]
+    fn other_devices_and_interfaces_are_ignored() {
+        let flash_drive = udev_message("add", "781/5567/100", DEVPATH, "usb_device");
+        let interface = udev_message("add", "8f2/6811/100", DEVPATH, "usb_interface");
+        assert!(TABLET.filter(&flash_drive).is_none());
+        assert!(TABLET.filter(&interface).is_none());
+    }
+
+    #[test]
+    fn malformed_messages_are_ignored() {
+        let mut truncated = udev_message("add", "8f2/6811/100", DEVPATH, "usb_device");
+        truncated.truncate(UDEV_HEADER_SIZE + 4);
+        assert!(TABLET.filter(&truncated).is_none());
+        assert!(TABLET.filter(b"libudev\0").is_none());
+        assert!(TABLET.filter(b"garbage").is_none());
+    }
+
+    #[test]
+    fn unicast_messages_are_dropped() {
+        let Ok(socket) = UeventSocket::open() else {
+            return; // sem suporte a netlink no ambiente de teste
+        };
+        let msg = udev_message("add", "8f2/6811/100", DEVPATH, "usb_device");
+
+        // SAFETY: chamadas diretas à API de sockets com estruturas locais.
+        unsafe {
+            let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+            let mut len = std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+            let ret = libc::getsockname(
+                socket.fd.as_raw_fd(),
+                &mut addr as *mut libc::sockaddr_nl as *mut libc::sockaddr,
+                &mut len,
+            );
+            assert_eq!(ret, 0);
+
+            let sender = libc::socket(libc::AF_NETLINK, libc::SOCK_RAW, libc::NETLINK_KOBJECT_UEVENT);
+            assert!(sender >= 0);
+            let sender = OwnedFd::from_raw_fd(sender);
+            let sent = libc::sendto(
+                sender.as_raw_fd(),
+                msg.as_ptr() as *const libc::c_void,
+                msg.len(),
+                0,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                len,
+            );
+            assert_eq!(sent, msg.len() as isize);
+        }
+
+        let mut buf = vec![0u8; 8192];
+        assert_eq!(socket.recv(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn root_hub_has_no_ports() {
+        assert_eq!(port_path("/devices/pci0000:00/0000:00:14.0/usb1"), Some(vec![]));
+    }
+}
@@ -0,0 +1,367 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use table_z_config::ButtonMapConfig;
+
+use crate::com::{protocol::DriverEvent, socket::Broadcaster};
+
+/// Etapa do aprendizado do botão atual.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    /// Aguardando o pacote do botão pressionado
+    WaitPress,
+    /// Aguardando o pacote de liberação do botão
+    WaitRelease,
+}
+
+/// Resultado do processamento de um pacote pelo [`ButtonLearner`].
+#[derive(Debug, Clone)]
+pub enum LearnProgress {
+    /// Nada mudou; continua aguardando.
+    Waiting,
+    /// Botão registrado; o usuário deve pressionar o botão indicado (a partir de 1).
+    Prompt(usize),
+    /// O pacote é igual ao de um botão já aprendido.
+    Duplicate { button: usize, same_as: usize },
+    /// Todos os botões foram aprendidos.
+    Finished(ButtonMapConfig),
+    /// Não foi possível montar uma tabela a partir dos pacotes recebidos.
+    Failed(String),
+}
+
+/// Aprende a assinatura dos botões de um tablet desconhecido.
+///
+/// Para cada botão, registra o pacote recebido ao pressioná-lo e o pacote
+/// seguinte do mesmo relatório (liberação). Ao final, as posições cujos bytes
+/// mudam entre pressionar e soltar algum botão formam `offsets`, e os valores
+/// nessas posições identificam cada botão.
+///
+/// Pacotes com conteúdo zerado (após o ID do relatório) são tratados como
+/// repouso. A caneta deve ficar afastada da mesa durante o aprendizado, pois
+/// qualquer outro pacote recebido é considerado.
+pub struct ButtonLearner {
+    /// Quantidade de botões a aprender
+    total: usize,
+    /// Pacote de cada botão pressionado
+    presses: Vec<Vec<u8>>,
+    /// Pacote de liberação de cada botão
+    releases: Vec<Vec<u8>>,
+    /// Etapa atual
+    step: Step,
+}
+
+impl ButtonLearner {
+    /// Cria um aprendizado para `total` botões.
+    pub fn new(total: usize) -> Self {
+        Self {
+            total,
+            presses: Vec::with_capacity(total),
+            releases: Vec::with_capacity(total),
+            step: Step::WaitPress,
+        }
+    }
+
+    /// Botão aguardado no momento (a partir de 1).
+    pub fn current_button(&self) -> usize {
+        self.presses.len() + 1
+    }
+
+    /// Processa um pacote recebido do dispositivo.
+    pub fn feed(&mut self, buf: &[u8]) -> LearnProgress {
+        if buf.is_empty() {
+            return LearnProgress::Waiting;
+        }
+
+        match self.step {
+            Step::WaitPress => {
+                // Pacotes de repouso (conteúdo zerado ou iguais a uma liberação
+                // já vista) são ignorados
+                if buf[1..].iter().all(|b| *b == 0) || self.releases.iter().any(|r| r.as_slice() == buf) {
+                    return LearnProgress::Waiting;
+                }
+
+                if let Some(j) = self.presses.iter().position(|p| p.as_slice() == buf) {
+                    return LearnProgress::Duplicate {
+                        button: self.current_button(),
+                        same_as: j + 1,
+                    };
+                }
+
+                self.presses.push(buf.to_vec());
+                self.step = Step::WaitRelease;
+                LearnProgress::Waiting
+            }
+            Step::WaitRelease => {
+                let press = self.presses.last().unwrap();
+                if buf[0] != press[0] || buf == press.as_slice() {
+                    return LearnProgress::Waiting;
+                }
+
+                self.releases.push(buf.to_vec());
+                self.step = Step::WaitPress;
+
+                if self.presses.len() < self.total {
+                    return LearnProgress::Prompt(self.current_button());
+                }
+
+                match self.finish() {
+                    Ok(map) => LearnProgress::Finished(map),
+                    Err(reason) => LearnProgress::Failed(reason),
+                }
+            }
+        }
+    }
+
+    /// Monta a tabela de botões a partir dos pacotes registrados.
+    fn finish(&self) -> Result<ButtonMapConfig, String> {
+        let report_id = self.presses[0][0];
+        if let Some(i) = self.presses.iter().position(|p| p[0] != report_id) {
+            return Err(format!(
+                "O botão {} usa o relatório {:#04x}, diferente do botão 1 ({:#04x})",
+                i + 1,
+                self.presses[i][0],
+                report_id
+            ));
+        }
+
+        // Posições que mudam entre pressionar e soltar algum botão
+        let mut offsets: Vec<usize> = Vec::new();
+        for (press, release) in self.presses.iter().zip(&self.releases) {
+            for offset in 1..press.len().min(release.len()) {
+                if press[offset] != release[offset] && !offsets.contains(&offset) {
+                    offsets.push(offset);
+                }
+            }
+        }
+        offsets.sort_unstable();
+
+        let buttons: Vec<Vec<u8>> = self
+            .presses
+            .iter()
+            .map(|p| offsets.iter().map(|o| p.get(*o).copied().unwrap_or(0)).collect())
+            .collect();
+
+        for (i, a) in buttons.iter().enumerate() {
+            if let Some(j) = buttons[i + 1..].iter().position(|b| b == a) {
+                return Err(format!(
+                    "Os botões {} e {} não podem ser distinguidos",
+                    i + 1,
+                    i + j + 2
+                ));
+            }
+        }
+
+        Ok(ButtonMapConfig {
+            report_id,
+            offsets,
+            buttons,
+        })
+    }
+}
+
+/// Controle compartilhado do modo de aprendizado de botões.
+///
+/// O loop principal inicia/cancela o aprendizado e recolhe o resultado com
+/// [`ButtonLearning::take_result`]; as threads de leitura entregam os pacotes com
+/// [`ButtonLearning::feed`]. O progresso é informado no terminal e aos clientes
+/// do socket como [`DriverEvent`].
+#[derive(Clone)]
+pub struct ButtonLearning {
+    /// Indica se há aprendizado em andamento
+    active: Arc<AtomicBool>,
+    /// Aprendizado em andamento
+    learner: Arc<Mutex<Option<ButtonLearner>>>,
+    /// Tabela aprendida, aguardando ser aplicada pelo loop principal
+    result: Arc<Mutex<Option<ButtonMapConfig>>>,
+    /// Canal de eventos para os clientes do socket
+    tx_socket: Broadcaster,
+}
+
+impl ButtonLearning {
+    /// Cria o controle, sem aprendizado ativo.
+    pub fn new(tx_socket: Broadcaster) -> Self {
+        Self {
+            active: Arc::new(AtomicBool::new(false)),
+            learner: Arc::new(Mutex::new(None)),
+            result: Arc::new(Mutex::new(None)),
+            tx_socket,
+        }
+    }
+
+    /// Informa o progresso no terminal e aos clientes do socket.
+    fn notify(&self, event: DriverEvent) {
+        match &event {
+            DriverEvent::LearnPrompt { button, total } => println!(
+                "🎓 Pressione e solte o botão {button} de {total} (mantenha a caneta afastada da mesa)"
+            ),
+            DriverEvent::LearnDuplicate { button, same_as } => println!(
+                "🎓 Pacote igual ao do botão {same_as}; pressione o botão {button}"
+            ),
+            DriverEvent::LearnFinished { button_map } => {
+                println!("🎓 Aprendizado concluído: {:?}", button_map)
+            }
+            DriverEvent::LearnCancelled => println!("🎓 Aprendizado cancelado."),
+            DriverEvent::LearnFailed { reason } => eprintln!("🎓 Aprendizado falhou: {reason}"),
+            _ => {}
+        }
+        self.tx_socket.send_json(&event);
+    }
+
+    /// Inicia (ou reinicia) o aprendizado de `count` botões.
+    pub fn start(&self, count: usize) {
+        if count == 0 {
+            self.notify(DriverEvent::LearnFailed {
+                reason: "Quantidade de botões deve ser maior que zero".into(),
+            });
+            return;
+        }
+
+        *self.learner.lock().unwrap() = Some(ButtonLearner::new(count));
+        self.active.store(true, Ordering::SeqCst);
+        self.notify(DriverEvent::LearnPrompt {
+            button: 1,
+            total: count,
+        });
+    }
+
+    /// Cancela o aprendizado em andamento, se houver.
+    pub fn cancel(&self) {
+        if self.learner.lock().unwrap().take().is_some() {
+            self.active.store(false, Ordering::SeqCst);
+            self.notify(DriverEvent::LearnCancelled);
+        }
+    }
+
+    /// Entrega um pacote ao aprendizado.
+    ///
+    /// Retorna `true` se o pacote foi consumido (aprendizado ativo), caso em
+    /// que ele não deve ser traduzido.
+    pub fn feed(&self, buf: &[u8]) -> bool {
+        if !self.active.load(Ordering::Relaxed) {
+            return false;
+        }
+
+        let mut guard = self.learner.lock().unwrap();
+        let Some(learner) = guard.as_mut() else {
+            return false;
+        };
+        let total = learner.total;
+
+        match learner.feed(buf) {
+            LearnProgress::Waiting => {}
+            LearnProgress::Prompt(button) => {
+                self.notify(DriverEvent::LearnPrompt { button, total })
+            }
+            LearnProgress::Duplicate { button, same_as } => {
+                self.notify(DriverEvent::LearnDuplicate { button, same_as })
+            }
+            LearnProgress::Finished(map) => {
+                *guard = None;
+                self.active.store(false, Ordering::SeqCst);
+                *self.result.lock().unwrap() = Some(map);
+            }
+            LearnProgress::Failed(reason) => {
+                *guard = None;
+                self.active.store(false, Ordering::SeqCst);
+                self.notify(DriverEvent::LearnFailed { reason });
+            }
+        }
+
+        true
+    }
+
+    /// Retorna a tabela aprendida, se o aprendizado tiver sido concluído.
+    pub fn take_result(&self) -> Option<ButtonMapConfig> {
+        self.result.lock().unwrap().take()
+    }
+
+    /// Informa aos clientes que a tabela aprendida não pôde ser aplicada.
+    pub fn notify_failed(&self, reason: String) {
+        self.notify(DriverEvent::LearnFailed { reason });
+    }
+
+    /// Informa aos clientes que a tabela aprendida foi aplicada.
+    pub fn notify_finished(&self, button_map: ButtonMapConfig) {
+        self.notify(DriverEvent::LearnFinished { button_map });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pressiona e solta um botão, retornando o progresso da liberação.
+    fn click(learner: &mut ButtonLearner, press: &[u8], release: &[u8]) -> LearnProgress {
+        assert!(matches!(learner.feed(press), LearnProgress::Waiting));
+        learner.feed(release)
+    }
+
+    #[test]
+    fn finish_derives_offsets_from_changed_bytes() {
+        let mut learner = ButtonLearner::new(3);
+        let rest = [2, 0, 0, 0, 0, 0, 0, 0];
+
+        assert!(matches!(
+            click(&mut learner, &[2, 1, 0, 86, 0, 0, 0, 0], &rest),
+            LearnProgress::Prompt(2)
+        ));
+        assert!(matches!(
+            click(&mut learner, &[2, 1, 0, 87, 0, 0, 0, 0], &rest),
+            LearnProgress::Prompt(3)
+        ));
+
+        let last = click(&mut learner, &[2, 0, 0, 47, 0, 0, 0, 0], &rest);
+        let LearnProgress::Finished(map) = last else {
+            panic!("aprendizado deveria terminar");
+        };
+        assert_eq!(map.report_id, 2);
+        assert_eq!(map.offsets, vec![1, 3]);
+        assert_eq!(map.buttons, vec![vec![1, 86], vec![1, 87], vec![0, 47]]);
+    }
+
+    #[test]
+    fn rest_packets_are_ignored_while_waiting_for_a_press() {
+        let mut learner = ButtonLearner::new(1);
+        assert!(matches!(learner.feed(&[2, 0, 0, 0]), LearnProgress::Waiting));
+        assert!(matches!(learner.feed(&[2, 1, 0, 0]), LearnProgress::Waiting));
+        assert_eq!(learner.current_button(), 2);
+    }
+
+    #[test]
+    fn repeated_press_is_reported_as_duplicate() {
+        let mut learner = ButtonLearner::new(2);
+        click(&mut learner, &[2, 1, 0, 86], &[2, 0, 0, 0]);
+
+        match learner.feed(&[2, 1, 0, 86]) {
+            LearnProgress::Duplicate { button, same_as } => {
+                assert_eq!((button, same_as), (2, 1));
+            }
+            other => panic!("esperado Duplicate, obtido {other:?}"),
+        }
+        assert_eq!(learner.current_button(), 2);
+    }
+
+    #[test]
+    fn indistinguishable_buttons_fail() {
+        // Os pacotes diferem apenas em uma posição que não muda ao soltar
+        let mut learner = ButtonLearner::new(2);
+        click(&mut learner, &[2, 1, 5, 0], &[2, 0, 5, 0]);
+
+        match click(&mut learner, &[2, 1, 6, 0], &[2, 0, 6, 0]) {
+            LearnProgress::Failed(reason) => assert!(reason.contains("1 e 2"), "{reason}"),
+            other => panic!("esperado Failed, obtido {other:?}"),
+        }
+    }
+
+    #[test]
+    fn buttons_on_different_reports_fail() {
+        let mut learner = ButtonLearner::new(2);
+        click(&mut learner, &[2, 1, 0, 0], &[2, 0, 0, 0]);
+
+        match click(&mut learner, &[3, 1, 0, 0], &[3, 0, 0, 0]) {
+            LearnProgress::Failed(reason) => assert!(reason.contains("botão 2"), "{reason}"),
+            other => panic!("esperado Failed, obtido {other:?}"),
+        }
+    }
+}
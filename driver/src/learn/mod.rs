@@ -0,0 +1,2 @@
+pub mod button_learner;
+pub use button_learner::{ButtonLearner, ButtonLearning, LearnProgress};
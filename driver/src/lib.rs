@@ -0,0 +1,15 @@
+//! Biblioteca do driver TableZ.
+//!
+//! Expõe os componentes usados pelo binário principal (`main.rs`) para que
+//! possam ser reutilizados por ferramentas auxiliares e *benchmarks*.
+#![allow(clippy::module_inception)]
+
+pub mod capture;
+pub mod com;
+pub mod hotplug;
+pub mod learn;
+pub mod reader;
+pub mod session;
+pub mod stats;
+pub mod translator;
+pub mod virtual_device;
@@ -1,59 +1,288 @@
 //! Ponto de entrada do sistema de emulação de tablet/tablet PC via USB.
 //!
 //! Este módulo integra:
-//! - Detecção automática (hotplug) de dispositivos USB compatíveis;
-//! - Leitura contínua dos pacotes HID via `USBReader`;
+//! - Detecção automática (hotplug) de dispositivos USB compatíveis, com uma
+//!   sessão independente por dispositivo (`SessionManager`);
+//! - Leitura contínua dos pacotes HID via `USBReader` (transferências assíncronas)
+//!   ou `HidrawReader` (`source.backend: hidraw`);
 //! - Tradução dos pacotes em comandos (`Translator` → `EmitCommand`);
-//! - Emulação de dispositivos virtuais (`VPen`, `VBtn`) usando `evdev`;
-//! - Comunicação via socket UNIX para controle e atualização de configuração.
+//! - Emulação de dispositivos virtuais (`VPen`, `VBtn`) usando `evdev`, via `EmitDispatcher`;
+//! - Comunicação via socket UNIX para controle e atualização de configuração,
+//!   reconectando ao dispositivo quando IDs, endpoints ou origem mudam;
+//! - Captura opcional dos pacotes crus (`capture:` na configuração ou `--capture <arquivo>`);
+//! - Modo de aprendizado de botões (comando `LearnButtons` via socket);
+//! - Encerramento limpo em SIGINT/SIGTERM, devolvendo o dispositivo ao kernel.
 
-mod com;
 use std::error::Error;
-mod hotplug;
-mod reader;
-mod translator;
-mod virtual_device;
 
-use evdev::Key;
-use std::str::FromStr;
 use std::path::Path;
 use std::sync::{
-    Arc, Mutex, OnceLock,
+    Arc, OnceLock,
     atomic::{AtomicBool, Ordering},
 };
-use serde_json;
+use std::time::{Duration, Instant};
 use anyhow::Result;
+use arc_swap::ArcSwap;
 
-use crate::{
-    com::socket::SocketServer,
-    hotplug::{HotPlugHandler, hotplug::CustomHotplugEvent},
-    reader::USBReader,
+use tablet_driver_rust::{
+    capture::PacketCapture,
+    com::{
+        protocol::{DriverEvent, SocketCommand},
+        socket::{Broadcaster, EmitFeed, SocketServer},
+    },
+    hotplug::{DeviceInfo, DeviceWatcher, HotPlugHandler, HotplugControl, WatchEvent},
+    learn::ButtonLearning,
+    reader::{HidrawReader, PacketSource, USBReader, discover_endpoints, find_hidraw},
+    session::{SessionManager, SessionState},
+    stats::{InputStats, StatsSnapshot},
     translator::{
         tablet_m100_translator::TabletM100Translator,
-        translator::{EmitCommand, Translator},
+        translator::{ConfigSnapshot, EmitCommand, Translator, publish_config},
     },
-    virtual_device::{VBtn, VPen},
+    virtual_device::{DeviceCapabilities, DevicePool, EmitDispatcher},
 };
 
-use table_z_config::Config;
+use table_z_config::{CaptureConfig, Config, SourceBackend};
+
+/// Sessões ativas, uma por dispositivo conectado (leitor, tradutor e dispositivos virtuais).
+static SESSIONS: OnceLock<SessionManager> = OnceLock::new();
+
+/// Sinalizado por SIGINT/SIGTERM para encerrar o driver de forma limpa.
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
 
-/// Sinal global usado para parar a thread de leitura USB ao desconectar o dispositivo.
-static STOP_FLAG: OnceLock<Mutex<Option<Arc<AtomicBool>>>> = OnceLock::new();
+/// Tempo máximo de espera pela liberação do dispositivo ao encerrar o driver.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Tentativas de localizar o nó hidraw após a conexão (intervalo de 100 ms).
+const HIDRAW_LOOKUP_ATTEMPTS: u32 = 10;
 
 /// Inicializa a estrutura global de controle (OnceLock).
 fn init_globals() {
-    STOP_FLAG.get_or_init(|| Mutex::new(None));
+    SESSIONS.get_or_init(SessionManager::new);
+}
+
+/// Gerenciador global de sessões.
+fn sessions() -> &'static SessionManager {
+    SESSIONS.get_or_init(SessionManager::new)
+}
+
+extern "C" fn on_shutdown_signal(_signal: libc::c_int) {
+    SHUTDOWN.store(true, Ordering::SeqCst);
+}
+
+/// Instala os tratadores de SIGINT e SIGTERM, que apenas sinalizam [`SHUTDOWN`].
+fn install_signal_handlers() {
+    let handler = on_shutdown_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: o tratador apenas grava em um atômico (async-signal-safe).
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+/// Remove as sessões cujos leitores já terminaram, informando o estado final aos clientes.
+fn report_finished_sessions(tx_socket: &Broadcaster, stats: &InputStats) {
+    for finished in sessions().reap_finished() {
+        stats.add_usb_errors(finished.errors);
+
+        println!(
+            "Sessão {}: leitor dos endpoints {:02x?} finalizado: {:?}",
+            finished.device, finished.endpoints, finished.exit
+        );
+        tx_socket.send_json(&DriverEvent::ReaderStopped {
+            device: finished.device,
+            endpoints: finished.endpoints,
+            exit: finished.exit,
+        });
+    }
+}
+
+/// Gera as estatísticas atuais, incluindo os erros dos leitores ativos.
+fn stats_snapshot(stats: &InputStats, tx_socket: &Broadcaster) -> StatsSnapshot {
+    stats.snapshot(sessions().error_count(), tx_socket.dropped_messages())
+}
+
+/// Encerra todas as sessões e aguarda a liberação das interfaces e do driver do kernel.
+fn stop_sessions_and_wait(timeout: Duration) {
+    sessions().stop_all();
+    if !sessions().wait_finished(timeout) {
+        eprintln!("⚠️ Tempo esgotado aguardando o encerramento da leitura USB.");
+    }
+}
+
+/// Tempo máximo de espera pelo início das sessões após uma reconexão.
+const REATTACH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Intervalo do loop principal enquanto há uma reconexão em andamento.
+const REATTACH_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Reconexão em andamento após a troca do dispositivo configurado.
+struct PendingReattach {
+    vendor_id: u16,
+    product_id: u16,
+    requested: Instant,
+    /// Momento da varredura pelo novo dispositivo, feita só depois que as
+    /// sessões anteriores terminaram
+    rescanned: Option<Instant>,
 }
 
-/// Retorna uma lista de todas as teclas válidas (0x000–0x2FF).
-fn all_keys() -> Vec<Key> {
-    (0..=0x2FFu16).map(Key::new).collect()
+impl PendingReattach {
+    /// Avança a reconexão e retorna o evento de resultado, quando houver.
+    ///
+    /// Enquanto restarem sessões anteriores, aguarda (por no máximo
+    /// `SHUTDOWN_TIMEOUT`, senão a reconexão falha); em seguida varre o
+    /// barramento e informa o resultado quando a varredura tiver terminado e
+    /// nenhuma sessão do novo dispositivo estiver sendo iniciada (ou o tempo se
+    /// esgotar).
+    fn poll(&mut self, hotplug: &HotplugControl) -> Option<DriverEvent> {
+        let Some(rescanned) = self.rescanned else {
+            if sessions().is_empty() {
+                hotplug.rescan(self.vendor_id, self.product_id);
+                self.rescanned = Some(Instant::now());
+                return None;
+            }
+            if self.requested.elapsed() < SHUTDOWN_TIMEOUT {
+                return None;
+            }
+            return Some(DriverEvent::ReattachFailed {
+                vendor_id: self.vendor_id,
+                product_id: self.product_id,
+                reason: "tempo esgotado aguardando o encerramento das sessões anteriores".into(),
+            });
+        };
+
+        let sessions: Vec<_> = sessions()
+            .list()
+            .into_iter()
+            .filter(|s| s.vendor_id == self.vendor_id && s.product_id == self.product_id)
+            .collect();
+
+        let settled = !hotplug.rescan_pending()
+            && sessions.iter().all(|s| s.state != SessionState::Starting);
+        if !settled && rescanned.elapsed() < REATTACH_TIMEOUT {
+            return None;
+        }
+
+        Some(DriverEvent::Reattached {
+            vendor_id: self.vendor_id,
+            product_id: self.product_id,
+            devices: sessions
+                .iter()
+                .filter(|s| s.state == SessionState::Running)
+                .map(|s| s.device)
+                .collect(),
+        })
+    }
+}
+
+/// Encerra as sessões atuais para reconectar ao dispositivo da nova configuração.
+///
+/// A varredura é feita por [`PendingReattach::poll`] só depois que as sessões
+/// terminarem, para que o mesmo dispositivo possa ser reaberto com os novos
+/// parâmetros (endpoints, backend, `init:`).
+fn reattach(cfg: &Config) -> PendingReattach {
+    println!(
+        "🔄 Dispositivo configurado alterado para {:04x}:{:04x}; reconectando...",
+        cfg.vendor_id, cfg.product_id
+    );
+
+    sessions().stop_all();
+    PendingReattach {
+        vendor_id: cfg.vendor_id,
+        product_id: cfg.product_id,
+        requested: Instant::now(),
+        rescanned: None,
+    }
+}
+
+/// Capacidades dos dispositivos virtuais para a configuração atual, anunciando
+/// apenas as teclas que o tradutor pode emitir.
+fn device_capabilities(
+    cfg: &Config,
+    settings: &ConfigSnapshot<<TabletM100Translator as Translator>::Settings>,
+) -> DeviceCapabilities {
+    DeviceCapabilities::from_config(cfg, &TabletM100Translator::emitted_keys(&settings.load()))
+}
+
+/// Lê a opção de linha de comando `--capture <arquivo>`.
+///
+/// Quando presente, a captura fica sempre ativa nesse arquivo, sobrepondo
+/// a seção `capture:` da configuração.
+fn capture_override_from_args() -> Option<CaptureConfig> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--capture" {
+            let path = args.next()?;
+            return Some(CaptureConfig {
+                enabled: true,
+                path,
+                ..CaptureConfig::default()
+            });
+        }
+    }
+    None
+}
+
+/// Inicia a leitura via libusb nos endpoints configurados ou descobertos,
+/// enviando antes a sequência `init:` da configuração.
+///
+/// As transferências são processadas pelo loop de eventos do hotplug.
+fn start_libusb<F>(
+    device: rusb::Device<rusb::Context>,
+    cfg: &Config,
+    handle_packet: F,
+) -> Result<Box<dyn PacketSource>>
+where
+    F: FnMut(u8, &[u8]) + Send + 'static,
+{
+    // Endpoints configurados ou descobertos nas interfaces HID
+    let endpoints = discover_endpoints(&device, cfg.endpoint, &cfg.endpoints)?;
+    println!("Lendo endpoints {:02x?}", endpoints);
+
+    let reader = USBReader::new()?.start(device, &endpoints, &cfg.init, handle_packet)?;
+    Ok(Box::new(reader))
+}
+
+/// Inicia a leitura via hidraw, mantendo o driver do kernel anexado.
+///
+/// O nó é `source.path`, se configurado, ou localizado via sysfs. Como o nó
+/// pode surgir alguns instantes após o evento de hotplug, a busca é repetida.
+fn start_hidraw<F>(cfg: &Config, handle_packet: F) -> Result<Box<dyn PacketSource>>
+where
+    F: FnMut(u8, &[u8]) + Send + 'static,
+{
+    if !cfg.init.is_empty() {
+        eprintln!("⚠️ A sequência `init:` só é enviada com o backend libusb; ignorada.");
+    }
+
+    let (path, endpoint) = match &cfg.source.path {
+        Some(path) => (path.into(), cfg.endpoint.unwrap_or(0)),
+        None => {
+            let mut attempts = 0;
+            let node = loop {
+                match find_hidraw(cfg.vendor_id, cfg.product_id, cfg.endpoint) {
+                    Ok(node) => break node,
+                    Err(_) if attempts < HIDRAW_LOOKUP_ATTEMPTS => {
+                        attempts += 1;
+                        std::thread::sleep(Duration::from_millis(100));
+                    }
+                    Err(e) => return Err(e),
+                }
+            };
+            println!("Nó hidraw selecionado: {node}");
+            (node.path, node.endpoint.unwrap_or(0))
+        }
+    };
+
+    let reader = HidrawReader::start(&path, endpoint, handle_packet)?;
+    Ok(Box::new(reader))
 }
 
 /// Função principal — inicializa o sistema, carrega a configuração e aguarda eventos de hotplug.
 fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     // Inicializa globals
     init_globals();
+    install_signal_handlers();
 
     // Cria o servidor de socket UNIX para comunicação externa
     let socket_server = SocketServer::new("/tmp/tablet.sock");
@@ -62,112 +291,146 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     // Carrega configuração principal
     let path = Path::new("/etc/table_z_utils.yaml");
     
-    let cfg = Config::from_file(path)?;
+    let mut cfg = Config::from_file(path)?;
 
     println!("Configuração carregada:\n{:#?}", cfg);
 
-    // Cria tradutor com a configuração inicial
-    let translator = Arc::new(Mutex::new(TabletM100Translator::new(Arc::new(Mutex::new(cfg.clone())))));
+    // Captura de pacotes crus (linha de comando tem prioridade sobre a configuração)
+    let capture_override = capture_override_from_args();
+    let capture = PacketCapture::default();
+    capture.apply(capture_override.as_ref().unwrap_or(&cfg.capture));
+
+    // Modo de aprendizado de botões, acionado via socket
+    let learning = ButtonLearning::new(tx_socket.clone());
+
+    // Estatísticas de entrada (comando `Status` via socket e registro periódico)
+    let stats = Arc::new(InputStats::new());
+    let mut last_stats_log = Instant::now();
+
+    // Snapshot de configuração compartilhado com os tradutores dos leitores
+    let settings: ConfigSnapshot<_> = Arc::new(ArcSwap::from_pointee(
+        TabletM100Translator::settings_from_config(&cfg)?,
+    ));
+
+    // Dispositivos virtuais mantidos entre reconexões
+    let device_pool = DevicePool::new();
+
+    // Configuração atual, lida pelo hotplug a cada evento
+    let current_cfg = Arc::new(ArcSwap::from_pointee(cfg.clone()));
+
+    // Reconexão solicitada por mudança do dispositivo configurado
+    let mut pending_reattach: Option<PendingReattach> = None;
 
     // Inicializa sistema de hotplug USB
-    HotPlugHandler::init({
-        let cfg = cfg.clone();
-        let translator = translator.clone();
-
-        move |device, event| match event {
-            CustomHotplugEvent::DeviceArrived => {
-                // Dados de configuração
-                let vendor_id: u16 = cfg.vendor_id;
-                let product_id: u16 = cfg.product_id;
-                let endpoint: u8 = cfg.interface;
-
-                println!(
-                    "Dispositivo conectado: {:?}",
-                    device.device_descriptor().unwrap()
-                );
-
-                let desc = device.device_descriptor().unwrap();
-
-                if desc.vendor_id() == vendor_id && desc.product_id() == product_id {
-                    println!("Dispositivo compatível detectado!");
-
-                    let usb_reader = USBReader::new().unwrap();
-                    let stop_flag = Arc::new(AtomicBool::new(true));
-
-                    // Define as teclas do dispositivo de caneta
-                    let pen_keys: Vec<Key> = vec![
-                        Key::from_str(&cfg.actions.pen).unwrap(),
-                        Key::from_str(&cfg.actions.stylus).unwrap(),
-                        Key::from_str(&cfg.actions.pen_touch).unwrap(),
-                    ];
-
-                    // Cria dispositivo virtual de caneta
-                    let vpen = VPen::new(
-                        cfg.pen.max_x as i32,
-                        cfg.pen.max_y as i32,
-                        cfg.pen.max_pressure as i32,
-                        cfg.pen.resolution_x as i32,
-                        cfg.pen.resolution_y as i32,
-                        &pen_keys,
-                        &cfg.xinput_name,
-                    ).unwrap();
-
-                    {
-                        let mut guard = STOP_FLAG.get_or_init(|| Mutex::new(None)).lock().unwrap();
-                        *guard = Some(stop_flag.clone());
+    let hotplug = HotPlugHandler::init(&cfg.hotplug, cfg.vendor_id, cfg.product_id, {
+        let current_cfg = current_cfg.clone();
+        let device_pool = device_pool.clone();
+        let settings = settings.clone();
+        let capture = capture.clone();
+        let learning = learning.clone();
+        let tx_socket = tx_socket.clone();
+        let stats = stats.clone();
+
+        // Dispositivos compatíveis conectados: apenas eles iniciam e encerram sessões
+        let mut watcher = DeviceWatcher::new(cfg.vendor_id, cfg.product_id);
+
+        move |device, event| {
+            let cfg = current_cfg.load_full();
+            watcher.set_target(cfg.vendor_id, cfg.product_id);
+
+            let info = match DeviceInfo::from_device(&device) {
+                Ok(info) => info,
+                Err(e) => {
+                    eprintln!("⚠️ Descritor do dispositivo indisponível: {:?}", e);
+                    return;
+                }
+            };
+
+            match watcher.handle(&info, event) {
+                Some(WatchEvent::Attached(id)) => {
+                    let key = id.key();
+                    println!("Dispositivo compatível detectado em {id}!");
+
+                    let (vendor_id, product_id) = (cfg.vendor_id, cfg.product_id);
+                    if !sessions().begin(key, id.port_path, vendor_id, product_id, cfg.source.backend) {
+                        println!("Sessão {key} já ativa; evento ignorado.");
+                        return;
                     }
 
-                    // Cria dispositivo virtual de botões
-                    let vbtn = VBtn::new(&all_keys(), &cfg.xinput_name).unwrap();
-
-                    // Clones necessários para thread
-                    let vbtn_clone = vbtn.clone();
-                    let vpen_clone = vpen.clone();
-                    let tx_socket = tx_socket.clone();
-                    let translator = translator.clone();
-
-                    // Inicia leitura contínua do USB
-                    usb_reader.start(device, endpoint, stop_flag.clone(), move |buf| {
-                        let emit_flow: Vec<EmitCommand> = translator.lock().unwrap().conv(&buf);
-
-                        for emit in emit_flow {
-                            match emit {
-                                EmitCommand::Pen { x, y, pressure, touch } => {
-                                    if let Err(e) = vpen_clone.emit(x, y, pressure, touch) {
-                                        eprintln!("Erro emitindo evento: {e}");
-                                    }
-
-                                    if let Ok(encoded) = serde_json::to_string(&emit) {
-                                        let _ = tx_socket.send(format!("{}\n", encoded).into_bytes());
-                                    }
-                                }
-                                EmitCommand::Btn { key, pressed, index: _ } => {
-                                    if let Err(e) = vbtn_clone.emit(Key::new(key as u16), pressed) {
-                                        eprintln!("Erro emitindo botão: {e}");
-                                    }
-
-                                    if let Ok(encoded) = serde_json::to_string(&emit) {
-                                        let _ = tx_socket.send(format!("{}\n", encoded).into_bytes());
-                                    }
-                                }
+                    // Dispositivos virtuais de caneta e de botões desta sessão, mantidos
+                    // entre reconexões pelo pool
+                    let caps = device_capabilities(&cfg, &settings);
+                    let mut dispatcher = match device_pool.acquire(caps) {
+                        Ok(lease) => EmitDispatcher::from_lease(lease),
+                        Err(e) => {
+                            eprintln!("❌ Sessão {key}: falha ao criar dispositivos virtuais: {e:#}");
+                            sessions().abort(key);
+                            return;
+                        }
+                    };
+
+                    // Clones necessários para o callback de leitura
+                    let tx_events = tx_socket.clone();
+                    let mut feed = EmitFeed::new(tx_socket.clone());
+                    let capture = capture.clone();
+                    let learning = learning.clone();
+                    let stats = stats.clone();
+
+                    // Tradutor exclusivo desta sessão
+                    let mut translator = TabletM100Translator::new(settings.clone());
+
+                    // Buffer de comandos reutilizado entre pacotes
+                    let mut emit_flow: Vec<EmitCommand> = Vec::with_capacity(16);
+
+                    // Processamento comum a todos os backends de leitura
+                    let handle_packet = move |endpoint: u8, buf: &[u8]| {
+                        let started = Instant::now();
+                        capture.record(endpoint, buf);
+
+                        // Durante o aprendizado os pacotes não são traduzidos
+                        let recognized = learning.feed(buf) || {
+                            emit_flow.clear();
+                            let recognized = translator.conv(buf, &mut emit_flow);
+                            dispatcher.dispatch(&emit_flow);
+                            feed.send(&emit_flow);
+                            recognized
+                        };
+
+                        stats.record_packet(buf, recognized, started.elapsed());
+                    };
+
+                    // Inicia a leitura fora do loop de eventos do hotplug: a sequência
+                    // `init:` usa transferências síncronas, que dependem desse loop
+                    std::thread::spawn(move || {
+                        let reader = match cfg.source.backend {
+                            SourceBackend::Libusb => start_libusb(device, &cfg, handle_packet),
+                            SourceBackend::Hidraw => start_hidraw(&cfg, handle_packet),
+                        };
+
+                        match reader {
+                            Ok(reader) => sessions().attach(key, reader),
+                            Err(e) => {
+                                eprintln!("❌ Sessão {key}: {e:#}");
+                                sessions().abort(key);
+                                tx_events.send_json(&DriverEvent::SessionFailed {
+                                    device: key,
+                                    reason: format!("{e:#}"),
+                                });
                             }
                         }
-                    }).unwrap();
+                    });
                 }
-            }
-            CustomHotplugEvent::DeviceLeft => {
-                println!("Dispositivo desconectado.");
-
-                // Interrompe thread de leitura USB
-                if let Some(flag) = STOP_FLAG
-                    .get_or_init(|| Mutex::new(None))
-                    .lock()
-                    .unwrap()
-                    .take()
-                {
-                    flag.store(false, Ordering::SeqCst);
-                    println!("Thread de leitura parada.");
+                Some(WatchEvent::Detached(id)) => {
+                    let key = id.key();
+                    println!("Dispositivo desconectado: {id}");
+
+                    // Encerra apenas a sessão deste dispositivo; o estado final é
+                    // informado pelo loop principal
+                    if sessions().stop(key) {
+                        println!("Leitura da sessão {key} interrompida.");
+                    }
                 }
+                None => {}
             }
         }
     });
@@ -175,18 +438,107 @@ fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     println!("Loop principal iniciado...");
 
     // Loop principal de escuta via socket
-    let translator_for_loop = translator.clone();
     loop {
+        if SHUTDOWN.load(Ordering::SeqCst) {
+            println!("Encerrando driver...");
+            stop_sessions_and_wait(SHUTDOWN_TIMEOUT);
+            return Ok(());
+        }
+
+        report_finished_sessions(&tx_socket, &stats);
+
+        // Reconexão: varre o barramento quando as sessões anteriores terminarem e
+        // informa o resultado assim que as sessões novas forem iniciadas
+        if let Some(pending) = &mut pending_reattach
+            && let Some(event) = pending.poll(&hotplug)
+        {
+            if let DriverEvent::ReattachFailed { reason, .. } = &event {
+                eprintln!("❌ Reconexão falhou: {reason}");
+            }
+            tx_socket.send_json(&event);
+            pending_reattach = None;
+        }
+
+        // Taxas de pacotes e registro periódico das estatísticas
+        stats.tick();
+        let log_interval = cfg.stats.log_interval_secs;
+        if log_interval > 0 && last_stats_log.elapsed() >= Duration::from_secs(log_interval) {
+            last_stats_log = Instant::now();
+            println!("📊 {}", stats_snapshot(&stats, &tx_socket));
+        }
+
         if let Some(cmd) = socket_server.try_recv_command() {
             println!("Comando recebido via socket: {}", cmd);
 
-            if let Ok(new_cfg) = serde_json::from_str::<Config>(&cmd) {
+            if let Ok(mut new_cfg) = serde_json::from_str::<Config>(&cmd) {
                 println!("Atualizando configuração em tempo de execução...");
-                let mut tr = translator_for_loop.lock().unwrap();
-                tr.update_from_config(&new_cfg);
+
+                // Clientes que não conhecem a tabela aprendida mantêm a atual
+                if new_cfg.button_map.is_none() {
+                    new_cfg.button_map = cfg.button_map.clone();
+                }
+
+                // O driver roda como root e o socket aceita qualquer usuário local:
+                // o arquivo de captura só é definido na inicialização
+                if new_cfg.capture.path != cfg.capture.path {
+                    eprintln!(
+                        "⚠️ capture.path não pode ser alterado pelo socket; mantendo {}",
+                        cfg.capture.path
+                    );
+                    new_cfg.capture.path = cfg.capture.path.clone();
+                }
+
+                // Em caso de erro, o snapshot anterior continua ativo
+                match publish_config::<TabletM100Translator>(&settings, &new_cfg) {
+                    Ok(()) => {
+                        capture.apply(capture_override.as_ref().unwrap_or(&new_cfg.capture));
+                        let device_changed = cfg.device_changed(&new_cfg);
+                        cfg = new_cfg;
+                        current_cfg.store(Arc::new(cfg.clone()));
+                        device_pool.reconfigure(&device_capabilities(&cfg, &settings));
+
+                        if device_changed {
+                            pending_reattach = Some(reattach(&cfg));
+                        }
+                    }
+                    Err(e) => eprintln!("Configuração rejeitada, mantendo a anterior: {e:#}"),
+                }
+            } else if let Ok(command) = serde_json::from_str::<SocketCommand>(cmd.trim()) {
+                match command {
+                    SocketCommand::LearnButtons { count } => learning.start(count),
+                    SocketCommand::CancelLearn => learning.cancel(),
+                    SocketCommand::Status => {
+                        tx_socket.send_json(&DriverEvent::Status(stats_snapshot(&stats, &tx_socket)))
+                    }
+                    SocketCommand::ListSessions => tx_socket.send_json(&DriverEvent::Sessions {
+                        sessions: sessions().list(),
+                    }),
+                }
+            }
+        }
+
+        // Aplica e persiste a tabela de botões aprendida (apenas a chave `button_map`
+        // do arquivo; o restante da configuração em memória pode ter vindo do socket)
+        if let Some(button_map) = learning.take_result() {
+            let mut new_cfg = cfg.clone();
+            new_cfg.button_map = Some(button_map.clone());
+
+            match publish_config::<TabletM100Translator>(&settings, &new_cfg) {
+                Ok(()) => {
+                    if let Err(e) = Config::save_button_map(path, &button_map) {
+                        eprintln!("Erro ao salvar tabela de botões: {e}");
+                    }
+                    cfg = new_cfg;
+                    current_cfg.store(Arc::new(cfg.clone()));
+                    device_pool.reconfigure(&device_capabilities(&cfg, &settings));
+                    learning.notify_finished(button_map);
+                }
+                Err(e) => learning.notify_failed(format!("Tabela de botões rejeitada: {e:#}")),
             }
         }
 
-        std::thread::sleep(std::time::Duration::from_secs(1));
+        // Com uma reconexão em andamento, o fim das sessões é verificado com mais frequência
+        let idle = if pending_reattach.is_some() { REATTACH_POLL_INTERVAL } else { Duration::from_secs(1) };
+        std::thread::sleep(idle);
     }
 }
@@ -0,0 +1,159 @@
+use anyhow::{Result, bail};
+use rusb::{Context, Device, Direction, TransferType};
+use std::fmt;
+
+/// Classe USB de dispositivos HID.
+const CLASS_HID: u8 = 0x03;
+
+/// Protocolo de interface HID sem *boot protocol* (nem teclado, nem mouse).
+const HID_PROTOCOL_NONE: u8 = 0x00;
+
+/// Endpoint de interrupção IN encontrado em uma interface HID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointCandidate {
+    /// Endereço do endpoint (ex: `0x81`)
+    pub address: u8,
+    /// Número da interface que contém o endpoint
+    pub interface: u8,
+    /// Subclasse HID da interface (`1` = boot)
+    pub sub_class: u8,
+    /// Protocolo HID da interface (`1` = teclado, `2` = mouse)
+    pub protocol: u8,
+    /// Tamanho máximo do pacote
+    pub max_packet_size: u16,
+}
+
+impl fmt::Display for EndpointCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.protocol {
+            1 => "teclado",
+            2 => "mouse",
+            _ => "genérico",
+        };
+        write!(
+            f,
+            "{:#04x} (interface {}, HID {}, {} bytes)",
+            self.address, self.interface, kind, self.max_packet_size
+        )
+    }
+}
+
+/// Lista os endpoints de interrupção IN das interfaces HID da configuração ativa.
+pub fn endpoint_candidates(device: &Device<Context>) -> Result<Vec<EndpointCandidate>> {
+    let config_desc = device.active_config_descriptor()?;
+    let mut candidates = Vec::new();
+
+    for interface in config_desc.interfaces() {
+        // Considera apenas a configuração alternativa padrão de cada interface
+        let Some(descriptor) = interface.descriptors().find(|d| d.setting_number() == 0) else {
+            continue;
+        };
+        if descriptor.class_code() != CLASS_HID {
+            continue;
+        }
+
+        for ep in descriptor.endpoint_descriptors() {
+            if ep.direction() == Direction::In && ep.transfer_type() == TransferType::Interrupt {
+                candidates.push(EndpointCandidate {
+                    address: ep.address(),
+                    interface: descriptor.interface_number(),
+                    sub_class: descriptor.sub_class_code(),
+                    protocol: descriptor.protocol_code(),
+                    max_packet_size: ep.max_packet_size(),
+                });
+            }
+        }
+    }
+
+    Ok(candidates)
+}
+
+/// Formata a lista de candidatos para mensagens de erro.
+pub fn describe_candidates(candidates: &[EndpointCandidate]) -> String {
+    if candidates.is_empty() {
+        return "nenhum endpoint de interrupção HID encontrado".into();
+    }
+    candidates
+        .iter()
+        .map(|c| format!("  - {c}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Define os endpoints a serem lidos no dispositivo.
+///
+/// Com `endpoints` configurado (lista `endpoints:`), todos são lidos, desde que
+/// sejam endpoints de interrupção IN existentes; senão, lê o único endpoint
+/// escolhido por [`discover_endpoint`].
+///
+/// # Erros
+/// Retorna erro listando os candidatos se algum endpoint configurado não
+/// existir ou se a descoberta automática falhar.
+pub fn discover_endpoints(
+    device: &Device<Context>,
+    endpoint: Option<u8>,
+    endpoints: &[u8],
+) -> Result<Vec<u8>> {
+    if endpoints.is_empty() {
+        return Ok(vec![discover_endpoint(device, endpoint)?]);
+    }
+
+    let candidates = endpoint_candidates(device)?;
+    for &endpoint in endpoints {
+        if !candidates.iter().any(|c| c.address == endpoint) {
+            bail!(
+                "Endpoint configurado {:#04x} não é um endpoint de interrupção HID deste dispositivo. Candidatos:\n{}",
+                endpoint,
+                describe_candidates(&candidates)
+            );
+        }
+    }
+    Ok(endpoints.to_vec())
+}
+
+/// Define o endpoint a ser lido no dispositivo.
+///
+/// Com `override_endpoint` (campo `endpoint:` da configuração), o valor é usado
+/// diretamente, desde que seja um endpoint de interrupção IN existente.
+/// Caso contrário, o endpoint é descoberto automaticamente:
+/// - Se houver um único endpoint de interrupção HID, ele é escolhido;
+/// - Havendo vários, interfaces de *boot* (teclado/mouse emulados pelo tablet)
+///   são descartadas e o único endpoint restante é escolhido.
+///
+/// # Erros
+/// Retorna erro listando os candidatos se o endpoint configurado não existir,
+/// se nenhum endpoint for encontrado ou se a escolha for ambígua.
+pub fn discover_endpoint(device: &Device<Context>, override_endpoint: Option<u8>) -> Result<u8> {
+    let candidates = endpoint_candidates(device)?;
+
+    if let Some(endpoint) = override_endpoint {
+        if candidates.iter().any(|c| c.address == endpoint) {
+            return Ok(endpoint);
+        }
+        bail!(
+            "Endpoint configurado {:#04x} não é um endpoint de interrupção HID deste dispositivo. Candidatos:\n{}",
+            endpoint,
+            describe_candidates(&candidates)
+        );
+    }
+
+    match candidates.as_slice() {
+        [] => bail!("Nenhum endpoint de interrupção HID encontrado no dispositivo"),
+        [single] => return Ok(single.address),
+        _ => {}
+    }
+
+    let generic: Vec<_> = candidates
+        .iter()
+        .filter(|c| c.protocol == HID_PROTOCOL_NONE)
+        .collect();
+
+    if let [single] = generic.as_slice() {
+        return Ok(single.address);
+    }
+
+    bail!(
+        "Vários endpoints candidatos; defina `endpoint:` na configuração. Candidatos:\n{}",
+        describe_candidates(&candidates)
+    )
+}
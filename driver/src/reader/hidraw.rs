@@ -0,0 +1,510 @@
+use anyhow::{Context, Result, bail};
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read};
+use std::os::fd::AsRawFd;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+use std::thread;
+
+use crate::reader::source::{PacketSource, ReaderExit};
+
+/// Diretório do sysfs com os nós hidraw do sistema.
+const SYSFS_HIDRAW: &str = "/sys/class/hidraw";
+
+/// Tamanho máximo de um relatório HID entregue pelo hidraw (`HID_MAX_BUFFER_SIZE`).
+const MAX_REPORT_SIZE: usize = 4096;
+
+/// Intervalo máximo entre verificações do pedido de encerramento, em milissegundos.
+const POLL_INTERVAL_MS: i32 = 200;
+
+/// Protocolo de interface HID sem *boot protocol* (nem teclado, nem mouse).
+const HID_PROTOCOL_NONE: u8 = 0x00;
+
+/// Nó `/dev/hidrawN` pertencente ao dispositivo procurado.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HidrawNode {
+    /// Caminho do nó (ex: `/dev/hidraw3`)
+    pub path: PathBuf,
+    /// Número da interface USB do nó
+    pub interface: u8,
+    /// Protocolo HID da interface (`1` = teclado, `2` = mouse)
+    pub protocol: u8,
+    /// Endpoint de interrupção IN da interface, se conhecido
+    pub endpoint: Option<u8>,
+}
+
+impl std::fmt::Display for HidrawNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.protocol {
+            1 => "teclado",
+            2 => "mouse",
+            _ => "genérico",
+        };
+        write!(
+            f,
+            "{} (interface {}, HID {}",
+            self.path.display(),
+            self.interface,
+            kind
+        )?;
+        if let Some(endpoint) = self.endpoint {
+            write!(f, ", endpoint {:#04x}", endpoint)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Lê um atributo hexadecimal do sysfs (ex: `bInterfaceNumber`).
+fn read_hex_attr(dir: &Path, name: &str) -> Option<u8> {
+    let value = fs::read_to_string(dir.join(name)).ok()?;
+    u8::from_str_radix(value.trim(), 16).ok()
+}
+
+/// Extrai vendor e product da linha `HID_ID=0003:000008F2:00006811` do `uevent`.
+fn parse_hid_id(uevent: &str) -> Option<(u16, u16)> {
+    let id = uevent.lines().find_map(|l| l.strip_prefix("HID_ID="))?;
+    let mut parts = id.split(':').skip(1);
+    let vendor = u32::from_str_radix(parts.next()?, 16).ok()?;
+    let product = u32::from_str_radix(parts.next()?, 16).ok()?;
+    Some((vendor as u16, product as u16))
+}
+
+/// Lista os nós hidraw do dispositivo `vendor_id:product_id` a partir de um
+/// diretório no formato de `/sys/class/hidraw`.
+pub fn hidraw_nodes_in(sysfs: &Path, vendor_id: u16, product_id: u16) -> Result<Vec<HidrawNode>> {
+    let entries = fs::read_dir(sysfs)
+        .with_context(|| format!("Erro ao listar {}", sysfs.display()))?;
+
+    let mut nodes = Vec::new();
+    for entry in entries.flatten() {
+        let hid_dir = entry.path().join("device");
+        let Ok(uevent) = fs::read_to_string(hid_dir.join("uevent")) else {
+            continue;
+        };
+        if parse_hid_id(&uevent) != Some((vendor_id, product_id)) {
+            continue;
+        }
+
+        // O diretório pai do dispositivo HID é a interface USB
+        let iface_dir = fs::canonicalize(&hid_dir)
+            .ok()
+            .and_then(|dir| dir.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+
+        let endpoint = fs::read_dir(&iface_dir).ok().and_then(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| {
+                    let name = e.file_name().into_string().ok()?;
+                    u8::from_str_radix(name.strip_prefix("ep_")?, 16).ok()
+                })
+                .find(|ep| ep & 0x80 != 0)
+        });
+
+        nodes.push(HidrawNode {
+            path: Path::new("/dev").join(entry.file_name()),
+            interface: read_hex_attr(&iface_dir, "bInterfaceNumber").unwrap_or(0),
+            protocol: read_hex_attr(&iface_dir, "bInterfaceProtocol").unwrap_or(0),
+            endpoint,
+        });
+    }
+
+    nodes.sort_by_key(|n| n.interface);
+    Ok(nodes)
+}
+
+/// Escolhe o nó hidraw a ser lido entre os encontrados.
+///
+/// Segue as mesmas regras de [`discover_endpoint`](crate::reader::discover_endpoint):
+/// com `endpoint` configurado, usa o nó da interface desse endpoint; senão,
+/// usa o único nó existente ou o único nó sem *boot protocol*.
+///
+/// # Erros
+/// Retorna erro listando os candidatos se a escolha não for possível.
+pub fn select_hidraw(nodes: Vec<HidrawNode>, endpoint: Option<u8>) -> Result<HidrawNode> {
+    let describe = |nodes: &[HidrawNode]| {
+        nodes
+            .iter()
+            .map(|n| format!("  - {n}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    if nodes.is_empty() {
+        bail!("Nenhum nó hidraw encontrado para o dispositivo");
+    }
+
+    if let Some(endpoint) = endpoint {
+        if let Some(node) = nodes.iter().find(|n| n.endpoint == Some(endpoint)) {
+            return Ok(node.clone());
+        }
+        bail!(
+            "Nenhum nó hidraw corresponde ao endpoint {:#04x}. Candidatos:\n{}",
+            endpoint,
+            describe(&nodes)
+        );
+    }
+
+    if nodes.len() == 1 {
+        return Ok(nodes[0].clone());
+    }
+
+    let generic: Vec<_> = nodes
+        .iter()
+        .filter(|n| n.protocol == HID_PROTOCOL_NONE)
+        .collect();
+    if let [single] = generic.as_slice() {
+        return Ok((*single).clone());
+    }
+
+    bail!(
+        "Vários nós hidraw candidatos; defina `endpoint:` ou `source.path` na configuração. Candidatos:\n{}",
+        describe(&nodes)
+    )
+}
+
+/// Localiza o nó `/dev/hidrawN` do dispositivo via sysfs.
+pub fn find_hidraw(vendor_id: u16, product_id: u16, endpoint: Option<u8>) -> Result<HidrawNode> {
+    find_hidraw_in(Path::new(SYSFS_HIDRAW), vendor_id, product_id, endpoint)
+}
+
+/// Localiza o nó do dispositivo em um diretório no formato de `/sys/class/hidraw`.
+pub fn find_hidraw_in(
+    sysfs: &Path,
+    vendor_id: u16,
+    product_id: u16,
+    endpoint: Option<u8>,
+) -> Result<HidrawNode> {
+    select_hidraw(hidraw_nodes_in(sysfs, vendor_id, product_id)?, endpoint)
+}
+
+/// Estado compartilhado entre a *thread* de leitura e seu [`HidrawHandle`].
+struct HidrawShared {
+    endpoints: Vec<u8>,
+    stopping: AtomicBool,
+    running: AtomicBool,
+    exit: Mutex<Option<ReaderExit>>,
+}
+
+/// Leitor de relatórios HID via `/dev/hidrawN`.
+///
+/// Diferente do [`USBReader`](crate::reader::USBReader), não desanexa o driver
+/// do kernel nem exige acesso direto ao USB: basta permissão de leitura no nó.
+/// Cada `read` do hidraw entrega exatamente um relatório, que é repassado ao
+/// *callback*. Qualquer arquivo com a mesma semântica (ex: um pipe em que cada
+/// escrita é um relatório) pode ser usado no lugar do nó, para testes.
+pub struct HidrawReader;
+
+/// Controle de uma leitura hidraw em execução, retornado por [`HidrawReader::start`].
+#[derive(Clone)]
+pub struct HidrawHandle {
+    shared: Arc<HidrawShared>,
+}
+
+impl PacketSource for HidrawHandle {
+    /// Encerra a leitura; a *thread* percebe o pedido em até 200 ms.
+    fn stop(&self) {
+        self.shared.stopping.store(true, Ordering::SeqCst);
+    }
+
+    fn is_running(&self) -> bool {
+        self.shared.running.load(Ordering::SeqCst)
+    }
+
+    fn exit_status(&self) -> Option<ReaderExit> {
+        self.shared.exit.lock().unwrap().clone()
+    }
+
+    fn endpoints(&self) -> &[u8] {
+        &self.shared.endpoints
+    }
+
+    /// Erros de leitura encerram a *thread*; a contagem é `1` após uma falha.
+    fn error_count(&self) -> u64 {
+        match self.exit_status() {
+            Some(ReaderExit::Failed { .. }) => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// Aguarda dados no descritor por até [`POLL_INTERVAL_MS`].
+///
+/// Retorna `Ok(true)` se houver algo a ler (dados, fim de arquivo ou erro).
+fn wait_readable(file: &File) -> std::io::Result<bool> {
+    let mut fds = libc::pollfd {
+        fd: file.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // SAFETY: `fds` é um único `pollfd` válido durante a chamada.
+    let rc = unsafe { libc::poll(&mut fds, 1, POLL_INTERVAL_MS) };
+    if rc < 0 {
+        let err = std::io::Error::last_os_error();
+        return if err.kind() == ErrorKind::Interrupted {
+            Ok(false)
+        } else {
+            Err(err)
+        };
+    }
+    Ok(rc > 0)
+}
+
+/// Laço de leitura; retorna o motivo do encerramento.
+fn read_loop<F>(mut file: File, shared: &HidrawShared, endpoint: u8, mut callback: F) -> ReaderExit
+where
+    F: FnMut(u8, &[u8]),
+{
+    let mut buf = vec![0u8; MAX_REPORT_SIZE];
+
+    while !shared.stopping.load(Ordering::SeqCst) {
+        match wait_readable(&file) {
+            Ok(false) => continue,
+            Ok(true) => {}
+            Err(e) => {
+                return ReaderExit::Failed {
+                    reason: format!("falha no poll: {e}"),
+                };
+            }
+        }
+
+        match file.read(&mut buf) {
+            // Fim de arquivo: nó removido ou escritor do pipe encerrado
+            Ok(0) => return ReaderExit::Disconnected,
+            Ok(len) => {
+                if shared.stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+                let packet = &buf[..len];
+                if catch_unwind(AssertUnwindSafe(|| callback(endpoint, packet))).is_err() {
+                    eprintln!("⚠️ Pânico no processamento do pacote; encerrando leitura.");
+                    return ReaderExit::Failed {
+                        reason: "pânico no processamento do pacote".into(),
+                    };
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::Interrupted | ErrorKind::WouldBlock) => {}
+            Err(e) if e.raw_os_error() == Some(libc::ENODEV) => return ReaderExit::Disconnected,
+            Err(e) => {
+                return ReaderExit::Failed {
+                    reason: format!("falha na leitura: {e}"),
+                };
+            }
+        }
+    }
+
+    ReaderExit::Stopped
+}
+
+impl HidrawReader {
+    /// Inicia a leitura contínua de um nó hidraw em uma *thread* dedicada.
+    ///
+    /// # Parâmetros
+    /// - `path`: Caminho do nó (ex: obtido com [`find_hidraw`]) ou de um pipe de teste.
+    /// - `endpoint`: Endpoint informado como origem dos pacotes (`0` se desconhecido).
+    /// - `callback`: Função chamada a cada relatório recebido, com `endpoint` como origem.
+    ///
+    /// # Erros
+    /// Retorna erro se o arquivo não puder ser aberto.
+    pub fn start<F>(path: &Path, endpoint: u8, callback: F) -> Result<HidrawHandle>
+    where
+        F: FnMut(u8, &[u8]) + Send + 'static,
+    {
+        let file = File::open(path)
+            .with_context(|| format!("Erro ao abrir {}", path.display()))?;
+
+        let shared = Arc::new(HidrawShared {
+            endpoints: vec![endpoint],
+            stopping: AtomicBool::new(false),
+            running: AtomicBool::new(true),
+            exit: Mutex::new(None),
+        });
+
+        println!("🟢 Leitura hidraw iniciada ({})", path.display());
+
+        thread::spawn({
+            let shared = shared.clone();
+            let path = path.to_path_buf();
+            move || {
+                // `read_loop` consome o callback: ele é descartado (devolvendo os
+                // recursos que captura) antes de o estado final ser publicado
+                let exit = read_loop(file, &shared, endpoint, callback);
+                println!(
+                    "🔴 Leitura hidraw encerrada ({}): {:?}",
+                    path.display(),
+                    exit
+                );
+                shared.running.store(false, Ordering::SeqCst);
+                *shared.exit.lock().unwrap() = Some(exit);
+            }
+        });
+
+        Ok(HidrawHandle { shared })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::io::Write;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::symlink;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    /// Diretório temporário removido ao final do teste.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("tablez_hidraw_{}_{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Cria `sysfs/<node>/device` apontando para um dispositivo HID sob a
+    /// interface USB `iface`, como no sysfs real.
+    fn add_node(
+        root: &Path,
+        node: &str,
+        hid_id: &str,
+        iface: &str,
+        number: u8,
+        protocol: u8,
+        endpoint: u8,
+    ) {
+        let iface_dir = root.join("devices").join(iface);
+        let hid_dir = iface_dir.join(format!("{hid_id}.0001"));
+        fs::create_dir_all(&hid_dir).unwrap();
+        fs::create_dir_all(iface_dir.join(format!("ep_{endpoint:02x}"))).unwrap();
+        fs::write(iface_dir.join("bInterfaceNumber"), format!("{number:02x}\n")).unwrap();
+        fs::write(iface_dir.join("bInterfaceProtocol"), format!("{protocol:02x}\n")).unwrap();
+        let uevent = format!("DRIVER=hid-generic\nHID_ID={hid_id}\n");
+        fs::write(hid_dir.join("uevent"), uevent).unwrap();
+
+        let node_dir = root.join("class").join(node);
+        fs::create_dir_all(&node_dir).unwrap();
+        symlink(&hid_dir, node_dir.join("device")).unwrap();
+    }
+
+    fn sysfs_tree(name: &str) -> TempDir {
+        let tmp = TempDir::new(name);
+        add_node(&tmp.0, "hidraw0", "0003:000008F2:00006811", "1-2:1.0", 0, 1, 0x81);
+        add_node(&tmp.0, "hidraw1", "0003:000008F2:00006811", "1-2:1.1", 1, 0, 0x82);
+        add_node(&tmp.0, "hidraw2", "0003:0000046D:0000C52B", "1-3:1.0", 0, 2, 0x81);
+        tmp
+    }
+
+    #[test]
+    fn finds_generic_node_of_the_device() {
+        let tmp = sysfs_tree("generic");
+        let node = find_hidraw_in(&tmp.0.join("class"), 0x08f2, 0x6811, None).unwrap();
+        assert_eq!(
+            node,
+            HidrawNode {
+                path: PathBuf::from("/dev/hidraw1"),
+                interface: 1,
+                protocol: 0,
+                endpoint: Some(0x82),
+            }
+        );
+    }
+
+    #[test]
+    fn configured_endpoint_selects_its_interface() {
+        let tmp = sysfs_tree("endpoint");
+        let node = find_hidraw_in(&tmp.0.join("class"), 0x08f2, 0x6811, Some(0x81)).unwrap();
+        assert_eq!(node.path, PathBuf::from("/dev/hidraw0"));
+        assert!(find_hidraw_in(&tmp.0.join("class"), 0x08f2, 0x6811, Some(0x83)).is_err());
+    }
+
+    #[test]
+    fn other_devices_are_not_matched() {
+        let tmp = sysfs_tree("other");
+        let nodes = hidraw_nodes_in(&tmp.0.join("class"), 0x046d, 0xc52b).unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].path, PathBuf::from("/dev/hidraw2"));
+        assert!(find_hidraw_in(&tmp.0.join("class"), 0x1234, 0x5678, None).is_err());
+    }
+
+    /// Cria um FIFO e inicia a leitura nele, retornando a ponta de escrita.
+    fn start_on_fifo<F>(tmp: &TempDir, callback: F) -> (HidrawHandle, File)
+    where
+        F: FnMut(u8, &[u8]) + Send + 'static,
+    {
+        let fifo = tmp.0.join("reports");
+        let c_path = CString::new(fifo.as_os_str().as_bytes()).unwrap();
+        // SAFETY: `c_path` é uma string C válida durante a chamada.
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+        // Abrir um FIFO bloqueia até a outra ponta ser aberta
+        let writer = thread::spawn({
+            let fifo = fifo.clone();
+            move || fs::OpenOptions::new().write(true).open(fifo).unwrap()
+        });
+        let handle = HidrawReader::start(&fifo, 0x81, callback).unwrap();
+        (handle, writer.join().unwrap())
+    }
+
+    /// Aguarda o fim da leitura.
+    fn wait_stopped(handle: &HidrawHandle) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while handle.is_running() {
+            assert!(Instant::now() < deadline, "leitura não terminou");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn reports_reach_the_callback_until_eof() {
+        let tmp = TempDir::new("eof");
+        let (tx, rx) = mpsc::channel();
+        let (handle, mut writer) = start_on_fifo(&tmp, move |endpoint, report| {
+            tx.send((endpoint, report.to_vec())).unwrap();
+        });
+        assert_eq!(handle.endpoints(), &[0x81]);
+
+        for report in [[2u8, 1, 0, 86], [2, 0, 0, 0]] {
+            writer.write_all(&report).unwrap();
+            let received = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(received, (0x81, report.to_vec()));
+        }
+
+        drop(writer);
+        wait_stopped(&handle);
+        assert_eq!(handle.exit_status(), Some(ReaderExit::Disconnected));
+        assert_eq!(handle.error_count(), 0);
+    }
+
+    #[test]
+    fn stop_ends_the_reading_and_drops_the_callback() {
+        let tmp = TempDir::new("stop");
+        let captured = Arc::new(());
+        let (handle, _writer) = start_on_fifo(&tmp, {
+            let captured = captured.clone();
+            move |_, _| {
+                let _ = &captured;
+            }
+        });
+        assert!(handle.is_running());
+
+        handle.stop();
+        wait_stopped(&handle);
+        assert_eq!(handle.exit_status(), Some(ReaderExit::Stopped));
+        assert_eq!(Arc::strong_count(&captured), 1);
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::{Result, bail};
+use rusb::{Context, DeviceHandle};
+use std::thread;
+use std::time::Duration;
+use table_z_config::{InitStep, InitStepKind};
+
+/// Tempo limite de cada transferência de controle da inicialização.
+const INIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// `bmRequestType` de um SET_REPORT (classe, interface, host → dispositivo).
+const HID_SET_REPORT_REQUEST_TYPE: u8 = 0x21;
+
+/// `bRequest` HID SET_REPORT.
+const HID_SET_REPORT: u8 = 0x09;
+
+/// Tipo de relatório *feature* (byte alto do `wValue`).
+const HID_REPORT_TYPE_FEATURE: u16 = 0x03;
+
+/// Descrição curta de um passo para os logs.
+fn describe(kind: &InitStepKind) -> String {
+    match kind {
+        InitStepKind::Control {
+            request_type,
+            request,
+            value,
+            index,
+            ..
+        } => format!(
+            "controle (tipo {:#04x}, request {:#04x}, value {:#06x}, index {})",
+            request_type, request, value, index
+        ),
+        InitStepKind::FeatureReport { report_id, data } => {
+            format!("feature report {} ({} bytes)", report_id, data.len())
+        }
+    }
+}
+
+/// Executa um único passo da inicialização.
+fn run_step(handle: &DeviceHandle<Context>, interface: u8, kind: &InitStepKind) -> Result<()> {
+    match kind {
+        InitStepKind::Control {
+            request_type,
+            request,
+            value,
+            index,
+            data,
+            length,
+        } => {
+            if request_type & 0x80 != 0 {
+                let mut buf = vec![0u8; *length];
+                let len = handle.read_control(
+                    *request_type,
+                    *request,
+                    *value,
+                    *index,
+                    &mut buf,
+                    INIT_TIMEOUT,
+                )?;
+                println!("   resposta: {:02x?}", &buf[..len]);
+            } else {
+                let len = handle.write_control(
+                    *request_type,
+                    *request,
+                    *value,
+                    *index,
+                    data,
+                    INIT_TIMEOUT,
+                )?;
+                if len != data.len() {
+                    bail!("enviados {} de {} bytes", len, data.len());
+                }
+            }
+        }
+        InitStepKind::FeatureReport { report_id, data } => {
+            let len = handle.write_control(
+                HID_SET_REPORT_REQUEST_TYPE,
+                HID_SET_REPORT,
+                (HID_REPORT_TYPE_FEATURE << 8) | *report_id as u16,
+                interface as u16,
+                data,
+                INIT_TIMEOUT,
+            )?;
+            if len != data.len() {
+                bail!("enviados {} de {} bytes", len, data.len());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Envia a sequência de inicialização configurada (`init:`) ao dispositivo.
+///
+/// Cada passo é registrado no terminal. Falhas em passos marcados como
+/// `optional` são apenas registradas; as demais interrompem a sequência.
+///
+/// As transferências são síncronas: não chame de dentro do loop de eventos
+/// da libusb (ex: diretamente no *callback* de hotplug).
+///
+/// # Erros
+/// Retorna erro no primeiro passo obrigatório que falhar.
+pub fn run_init_sequence(
+    handle: &DeviceHandle<Context>,
+    interface: u8,
+    steps: &[InitStep],
+) -> Result<()> {
+    for (i, step) in steps.iter().enumerate() {
+        let description = describe(&step.kind);
+
+        match run_step(handle, interface, &step.kind) {
+            Ok(()) => println!("⚙️ Init {}/{}: {} OK", i + 1, steps.len(), description),
+            Err(e) if step.optional => eprintln!(
+                "⚠️ Init {}/{}: {} falhou (opcional): {e:#}",
+                i + 1,
+                steps.len(),
+                description
+            ),
+            Err(e) => bail!(
+                "Init {}/{}: {} falhou: {e:#}",
+                i + 1,
+                steps.len(),
+                description
+            ),
+        }
+
+        if step.delay_ms > 0 {
+            thread::sleep(Duration::from_millis(step.delay_ms));
+        }
+    }
+    Ok(())
+}
@@ -1,2 +1,9 @@
+pub mod discovery;
+pub mod hidraw;
+pub mod init;
 pub mod reader;
-pub use reader::USBReader;
\ No newline at end of file
+pub mod source;
+pub use discovery::{EndpointCandidate, discover_endpoint, discover_endpoints};
+pub use hidraw::{HidrawHandle, HidrawReader, find_hidraw};
+pub use reader::{ReaderHandle, USBReader};
+pub use source::{PacketSource, ReaderExit};
@@ -1,121 +1,590 @@
-use rusb::{Context, Device, Error as UsbError};
+use rusb::{Context, Device, DeviceHandle, UsbContext};
 use anyhow::{bail, Result};
+use libusb1_sys::{
+    constants::{
+        LIBUSB_ERROR_NO_DEVICE, LIBUSB_TRANSFER_CANCELLED, LIBUSB_TRANSFER_COMPLETED,
+        LIBUSB_TRANSFER_ERROR, LIBUSB_TRANSFER_NO_DEVICE, LIBUSB_TRANSFER_OVERFLOW,
+        LIBUSB_TRANSFER_STALL, LIBUSB_TRANSFER_TIMED_OUT,
+    },
+    libusb_alloc_transfer, libusb_cancel_transfer, libusb_fill_interrupt_transfer,
+    libusb_free_transfer, libusb_submit_transfer, libusb_transfer,
+};
+use std::os::raw::{c_int, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+    Arc, Mutex, PoisonError,
 };
 use std::thread;
-use std::time::Duration;
 
-/// Estrutura responsável por realizar leituras contínuas de um endpoint USB.
+use table_z_config::InitStep;
+
+use crate::reader::discovery::{describe_candidates, endpoint_candidates};
+use crate::reader::init::run_init_sequence;
+use crate::reader::source::{PacketSource, ReaderExit};
+
+/// Quantidade de transferências de interrupção mantidas em andamento por endpoint.
+///
+/// Com vários buffers submetidos, o próximo pacote já tem onde ser recebido
+/// enquanto o anterior é processado.
+const TRANSFERS_IN_FLIGHT: usize = 4;
+
+/// Quantidade de erros transitórios consecutivos tolerados antes de encerrar o leitor.
+const MAX_TRANSIENT_ERRORS: u32 = 5;
+
+/// Callback do usuário, chamado a cada pacote recebido com o endpoint de origem.
+type PacketCallback = Box<dyn FnMut(u8, &[u8]) + Send>;
+
+/// Interface reivindicada (*claim*) pelo leitor.
+struct ClaimedInterface {
+    /// Número da interface
+    number: u8,
+    /// Indica que o driver do kernel foi desanexado e deve ser reanexado ao encerrar
+    reattach_kernel_driver: bool,
+}
+
+/// Endpoint a ser lido, com os dados obtidos dos descritores.
+struct EndpointTarget {
+    address: u8,
+    interface: u8,
+    max_packet_size: usize,
+}
+
+/// Formata uma lista de endpoints para os logs (ex: `0x81, 0x85`).
+fn format_endpoints(endpoints: &[u8]) -> String {
+    endpoints
+        .iter()
+        .map(|ep| format!("{:#04x}", ep))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Libera as interfaces e reanexa o driver do kernel onde ele foi desanexado.
+fn release_interfaces(handle: &DeviceHandle<Context>, interfaces: &[ClaimedInterface]) {
+    for iface in interfaces {
+        // Falhas são esperadas se o dispositivo já foi removido
+        handle.release_interface(iface.number).ok();
+
+        if iface.reattach_kernel_driver {
+            match handle.attach_kernel_driver(iface.number) {
+                Ok(()) => println!("Driver do kernel reanexado (interface {})", iface.number),
+                Err(rusb::Error::NoDevice) => {}
+                Err(e) => eprintln!(
+                    "⚠️ Falha ao reanexar o driver do kernel (interface {}): {e}",
+                    iface.number
+                ),
+            }
+        }
+    }
+}
+
+/// Estado compartilhado entre as transferências de um leitor e seu [`ReaderHandle`].
+struct ReaderShared {
+    /// Handle do dispositivo, mantido aberto enquanto houver transferências
+    handle: DeviceHandle<Context>,
+    /// Endpoints lidos
+    endpoints: Vec<u8>,
+    /// Interfaces reivindicadas (*claim*) para a leitura
+    interfaces: Vec<ClaimedInterface>,
+    /// Callback do usuário
+    callback: Mutex<PacketCallback>,
+    /// Indica que o leitor está sendo encerrado (transferências não são ressubmetidas)
+    stopping: AtomicBool,
+    /// Erros transitórios consecutivos
+    errors: AtomicU32,
+    /// Total de erros de leitura desde o início
+    errors_total: AtomicU64,
+    /// Motivo do encerramento, registrado pela primeira causa
+    cause: Mutex<Option<ReaderExit>>,
+    /// Estado final, preenchido quando todos os recursos foram liberados
+    exit: Mutex<Option<ReaderExit>>,
+    /// Transferências em andamento (ponteiros `*mut libusb_transfer`), usadas no cancelamento
+    in_flight: Mutex<Vec<usize>>,
+}
+
+impl ReaderShared {
+    /// Inicia o encerramento do leitor, cancelando as transferências em andamento.
+    ///
+    /// Apenas a primeira causa é registrada; chamadas seguintes não fazem nada.
+    fn shutdown(&self, exit: ReaderExit) {
+        {
+            let mut cause = self.cause.lock().unwrap();
+            if cause.is_some() {
+                return;
+            }
+            *cause = Some(exit);
+        }
+        self.stopping.store(true, Ordering::SeqCst);
+
+        let in_flight = self.in_flight.lock().unwrap();
+        for transfer in in_flight.iter() {
+            // SAFETY: transferências só são liberadas após saírem de `in_flight`,
+            // sob o mesmo lock. Cancelar uma transferência que não está submetida
+            // apenas retorna erro.
+            unsafe {
+                libusb_cancel_transfer(*transfer as *mut libusb_transfer);
+            }
+        }
+    }
+
+    /// Libera as interfaces, descarta o *callback*, reanexa o driver do kernel e
+    /// registra o estado final.
+    ///
+    /// Chamado uma única vez, quando a última transferência é liberada. O
+    /// *callback* é descartado aqui, e não quando o handle deixa de existir,
+    /// para que os recursos capturados por ele (ex: os dispositivos virtuais
+    /// emprestados do *pool*) sejam devolvidos assim que a leitura termina.
+    fn teardown(&self) {
+        release_interfaces(&self.handle, &self.interfaces);
+
+        let callback: PacketCallback = Box::new(|_, _| {});
+        drop(std::mem::replace(
+            &mut *self.callback.lock().unwrap_or_else(PoisonError::into_inner),
+            callback,
+        ));
+
+        let exit = self
+            .cause
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or(ReaderExit::Stopped);
+
+        println!(
+            "🔴 Leitura encerrada (endpoints {}): {:?}",
+            format_endpoints(&self.endpoints),
+            exit
+        );
+        *self.exit.lock().unwrap() = Some(exit);
+    }
+}
+
+/// Dados de uma transferência individual, apontados por `user_data`.
+struct TransferSlot {
+    shared: Arc<ReaderShared>,
+    endpoint: u8,
+    buf: Vec<u8>,
+}
+
+/// Estrutura responsável por realizar leituras contínuas de endpoints USB.
+///
+/// A leitura usa transferências de interrupção **assíncronas** da libusb, com
+/// vários buffers em andamento. Não há *thread* própria: as transferências são
+/// concluídas pelo mesmo loop de eventos do contexto USB (o loop de hotplug),
+/// que chama o *callback* fornecido pelo usuário a cada pacote.
 ///
-/// A leitura ocorre em uma *thread* separada e envia os dados recebidos
-/// para o *callback* fornecido pelo usuário.
+/// Um leitor pode reivindicar várias interfaces e ler vários endpoints (ex:
+/// caneta em uma interface e botões em outra). Como todas as conclusões
+/// passam pelo mesmo loop de eventos, os pacotes chegam ao *callback* em um
+/// único fluxo ordenado, cada um acompanhado do endpoint de origem.
+///
+/// Erros transitórios (*stall*, *overflow*, erro de transferência) são
+/// tolerados até [`MAX_TRANSIENT_ERRORS`] vezes seguidas; em um *stall* o
+/// endpoint é desbloqueado (`clear_halt`) antes de nova tentativa. Ao
+/// encerrar, a interface é liberada e o driver do kernel é reanexado.
 ///
 /// Essa implementação é voltada principalmente para dispositivos HID
 /// ou dispositivos que usam *interrupt endpoints* para envio periódico de dados.
 pub struct USBReader;
 
+/// Controle de um leitor em execução, retornado por [`USBReader::start`].
+///
+/// Encerrar o leitor cancela imediatamente as transferências em andamento.
+#[derive(Clone)]
+pub struct ReaderHandle {
+    shared: Arc<ReaderShared>,
+}
+
+impl PacketSource for ReaderHandle {
+    /// Encerra a leitura, cancelando todas as transferências em andamento.
+    ///
+    /// Os recursos são liberados pelo loop de eventos assim que cada
+    /// cancelamento for confirmado.
+    fn stop(&self) {
+        self.shared.shutdown(ReaderExit::Stopped);
+    }
+
+    /// Retorna `true` enquanto houver transferências em andamento.
+    fn is_running(&self) -> bool {
+        !self.shared.in_flight.lock().unwrap().is_empty()
+    }
+
+    fn exit_status(&self) -> Option<ReaderExit> {
+        self.shared.exit.lock().unwrap().clone()
+    }
+
+    fn endpoints(&self) -> &[u8] {
+        &self.shared.endpoints
+    }
+
+    fn error_count(&self) -> u64 {
+        self.shared.errors_total.load(Ordering::Relaxed)
+    }
+}
+
+/// Nome legível de um status de transferência da libusb.
+fn status_name(status: c_int) -> String {
+    match status {
+        LIBUSB_TRANSFER_ERROR => "erro de transferência".into(),
+        LIBUSB_TRANSFER_STALL => "endpoint bloqueado (stall)".into(),
+        LIBUSB_TRANSFER_OVERFLOW => "overflow".into(),
+        LIBUSB_TRANSFER_NO_DEVICE => "dispositivo removido".into(),
+        other => format!("status {other}"),
+    }
+}
+
+/// Libera uma transferência que não será mais ressubmetida.
+///
+/// Ao liberar a última transferência, conclui o encerramento do leitor.
+///
+/// # Safety
+/// `transfer` deve ter sido criada por [`submit_transfers`] e não estar em andamento.
+unsafe fn release_transfer(transfer: *mut libusb_transfer) {
+    unsafe {
+        let slot = Box::from_raw((*transfer).user_data as *mut TransferSlot);
+        let remaining = {
+            let mut in_flight = slot.shared.in_flight.lock().unwrap();
+            in_flight.retain(|t| *t != transfer as usize);
+            in_flight.len()
+        };
+        libusb_free_transfer(transfer);
+
+        if remaining == 0 {
+            slot.shared.teardown();
+        }
+    }
+}
+
+/// Ressubmete a transferência, a menos que o leitor esteja sendo encerrado.
+///
+/// Feito sob o lock de `in_flight`, o mesmo usado por [`ReaderShared::shutdown`]
+/// ao cancelar, para que nenhuma transferência escape do cancelamento.
+/// Retorna `false` se a transferência não foi ressubmetida.
+///
+/// # Safety
+/// `transfer` deve ter sido criada por [`submit_transfers`] e não estar em andamento.
+unsafe fn resubmit(shared: &ReaderShared, transfer: *mut libusb_transfer) -> bool {
+    let rc = {
+        let _guard = shared.in_flight.lock().unwrap();
+        if shared.stopping.load(Ordering::SeqCst) {
+            return false;
+        }
+        unsafe { libusb_submit_transfer(transfer) }
+    };
+
+    match rc {
+        0 => true,
+        LIBUSB_ERROR_NO_DEVICE => {
+            shared.shutdown(ReaderExit::Disconnected);
+            false
+        }
+        rc => {
+            shared.errors_total.fetch_add(1, Ordering::Relaxed);
+            shared.shutdown(ReaderExit::Failed {
+                reason: format!("falha ao ressubmeter transferência (código {rc})"),
+            });
+            false
+        }
+    }
+}
+
+/// Desbloqueia o endpoint após um *stall* e ressubmete a transferência.
+///
+/// `clear_halt` é síncrono e não pode ser chamado de dentro do loop de
+/// eventos, por isso roda em uma *thread* auxiliar.
+fn clear_halt_and_resubmit(shared: Arc<ReaderShared>, endpoint: u8, transfer: *mut libusb_transfer) {
+    let transfer = transfer as usize;
+    thread::spawn(move || {
+        let transfer = transfer as *mut libusb_transfer;
+
+        if let Err(e) = shared.handle.clear_halt(endpoint) {
+            shared.shutdown(match e {
+                rusb::Error::NoDevice => ReaderExit::Disconnected,
+                e => ReaderExit::Failed {
+                    reason: format!("falha ao desbloquear endpoint: {e}"),
+                },
+            });
+        }
+
+        // SAFETY: a transferência não está submetida enquanto aguarda o `clear_halt`.
+        unsafe {
+            if !resubmit(&shared, transfer) {
+                release_transfer(transfer);
+            }
+        }
+    });
+}
+
+/// Chamado pelo loop de eventos da libusb ao concluir uma transferência.
+extern "system" fn transfer_callback(transfer: *mut libusb_transfer) {
+    // SAFETY: `user_data` aponta para o `TransferSlot` criado em `submit_transfers`,
+    // válido até `release_transfer`.
+    unsafe {
+        let slot = &mut *((*transfer).user_data as *mut TransferSlot);
+        let shared = slot.shared.clone();
+        let status = (*transfer).status;
+
+        match status {
+            LIBUSB_TRANSFER_COMPLETED => {
+                shared.errors.store(0, Ordering::SeqCst);
+
+                if !shared.stopping.load(Ordering::SeqCst) {
+                    let len = ((*transfer).actual_length.max(0) as usize).min(slot.buf.len());
+                    let packet = &slot.buf[..len];
+                    let result = catch_unwind(AssertUnwindSafe(|| {
+                        (shared.callback.lock().unwrap())(slot.endpoint, packet)
+                    }));
+                    if result.is_err() {
+                        eprintln!("⚠️ Pânico no processamento do pacote; encerrando leitura.");
+                        shared.shutdown(ReaderExit::Failed {
+                            reason: "pânico no processamento do pacote".into(),
+                        });
+                    }
+                }
+            }
+            LIBUSB_TRANSFER_TIMED_OUT | LIBUSB_TRANSFER_CANCELLED => {}
+            LIBUSB_TRANSFER_NO_DEVICE => shared.shutdown(ReaderExit::Disconnected),
+            LIBUSB_TRANSFER_STALL | LIBUSB_TRANSFER_OVERFLOW | LIBUSB_TRANSFER_ERROR => {
+                shared.errors_total.fetch_add(1, Ordering::Relaxed);
+                let errors = shared.errors.fetch_add(1, Ordering::SeqCst) + 1;
+                if errors > MAX_TRANSIENT_ERRORS {
+                    shared.shutdown(ReaderExit::Failed {
+                        reason: format!(
+                            "{} erros consecutivos (último: {})",
+                            errors,
+                            status_name(status)
+                        ),
+                    });
+                } else {
+                    eprintln!(
+                        "⚠️ Erro transitório na leitura USB (endpoint {:#04x}): {} (tentativa {}/{})",
+                        slot.endpoint,
+                        status_name(status),
+                        errors,
+                        MAX_TRANSIENT_ERRORS
+                    );
+                    if status == LIBUSB_TRANSFER_STALL {
+                        clear_halt_and_resubmit(shared, slot.endpoint, transfer);
+                        return;
+                    }
+                }
+            }
+            status => {
+                shared.errors_total.fetch_add(1, Ordering::Relaxed);
+                shared.shutdown(ReaderExit::Failed {
+                    reason: status_name(status),
+                });
+            }
+        }
+
+        // Ressubmete o mesmo buffer enquanto o leitor estiver ativo
+        if status != LIBUSB_TRANSFER_CANCELLED && resubmit(&shared, transfer) {
+            return;
+        }
+
+        release_transfer(transfer);
+    }
+}
+
+/// Aloca e submete as transferências de interrupção de cada endpoint do leitor.
+fn submit_transfers(shared: &Arc<ReaderShared>, targets: &[EndpointTarget]) -> Result<()> {
+    let slots = targets
+        .iter()
+        .flat_map(|target| std::iter::repeat_n(target, TRANSFERS_IN_FLIGHT));
+
+    for target in slots {
+        let max_packet_size = target.max_packet_size;
+        // SAFETY: a transferência e seu `TransferSlot` permanecem válidos até
+        // `release_transfer`, chamado pelo callback ou em caso de falha aqui.
+        unsafe {
+            let transfer = libusb_alloc_transfer(0);
+            if transfer.is_null() {
+                shared.shutdown(ReaderExit::Failed {
+                    reason: "falha ao alocar transferência USB".into(),
+                });
+                if shared.in_flight.lock().unwrap().is_empty() {
+                    shared.teardown();
+                }
+                bail!("Falha ao alocar transferência USB");
+            }
+
+            let slot = Box::into_raw(Box::new(TransferSlot {
+                shared: shared.clone(),
+                endpoint: target.address,
+                buf: vec![0u8; max_packet_size],
+            }));
+
+            libusb_fill_interrupt_transfer(
+                transfer,
+                shared.handle.as_raw(),
+                target.address,
+                (*slot).buf.as_mut_ptr(),
+                max_packet_size as i32,
+                transfer_callback,
+                slot as *mut c_void,
+                0,
+            );
+
+            shared.in_flight.lock().unwrap().push(transfer as usize);
+
+            let rc = libusb_submit_transfer(transfer);
+            if rc != 0 {
+                shared.shutdown(ReaderExit::Failed {
+                    reason: format!("falha ao submeter transferência USB (código {rc})"),
+                });
+                release_transfer(transfer);
+                bail!("Falha ao submeter transferência USB (código {rc})");
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl USBReader {
     /// Cria uma nova instância do leitor USB.
     ///
     /// # Exemplo
-    /// ```
+    /// ```ignore
     /// let reader = USBReader::new()?;
     /// ```
     pub fn new() -> Result<Self> {
         Ok(USBReader)
     }
 
-    /// Inicia a leitura contínua de um endpoint USB.
+    /// Inicia a leitura contínua de um ou mais endpoints USB.
     ///
     /// Este método:
-    /// - Localiza o endpoint informado.
-    /// - Detacha o *kernel driver* (se necessário).
-    /// - Faz o *claim* da interface correspondente.
-    /// - Inicia uma *thread* que realiza leituras periódicas até que `stop_flag` seja `false`.
+    /// - Localiza os endpoints informados e suas interfaces.
+    /// - Detacha o *kernel driver* de cada interface (se necessário).
+    /// - Faz o *claim* das interfaces correspondentes.
+    /// - Envia a sequência de inicialização `init` (se houver).
+    /// - Submete transferências assíncronas que são ressubmetidas até o leitor ser encerrado.
+    ///
+    /// As transferências são processadas pelo loop de eventos do contexto do
+    /// `device` (ex: o loop de [`HotPlugHandler`](crate::hotplug::HotPlugHandler)).
     ///
     /// # Parâmetros
     /// - `device`: Dispositivo USB já detectado via `rusb`.
-    /// - `endpoint`: Endereço do endpoint a ser lido (ex: `0x81`).
-    /// - `stop_flag`: Flag atômica que controla o loop de leitura (quando `false`, encerra).
-    /// - `callback`: Função que será chamada sempre que um pacote for recebido.
+    /// - `endpoints`: Endereços dos endpoints a serem lidos (ex: `[0x81, 0x85]`),
+    ///   normalmente obtidos com [`discover_endpoints`](crate::reader::discover_endpoints).
+    /// - `init`: Passos de inicialização (`init:` da configuração), enviados à
+    ///   interface do primeiro endpoint. Como são transferências síncronas, com
+    ///   passos configurados este método não deve ser chamado de dentro do loop de eventos.
+    /// - `callback`: Função chamada a cada pacote recebido, com o endpoint de origem.
+    ///   O slice recebido aponta para um buffer reutilizado entre leituras.
     ///
     /// # Retorno
-    /// Retorna `Ok(())` se a thread de leitura foi iniciada com sucesso.
+    /// Retorna um [`ReaderHandle`] para encerrar a leitura e consultar seu estado final.
     ///
     /// # Erros
-    /// Retorna erro (`anyhow::Error`) se o endpoint não for encontrado
-    /// ou se ocorrer falha na abertura ou *claim* da interface.
+    /// Retorna erro (`anyhow::Error`) se algum endpoint não for encontrado
+    /// ou se ocorrer falha na abertura, *claim* das interfaces, em um passo
+    /// obrigatório da inicialização ou na submissão das transferências.
     pub fn start<F>(
         &self,
         device: Device<Context>,
-        endpoint: u8,
-        stop_flag: Arc<AtomicBool>,
-        mut callback: F,
-    ) -> Result<()>
+        endpoints: &[u8],
+        init: &[InitStep],
+        callback: F,
+    ) -> Result<ReaderHandle>
     where
-        F: FnMut(Vec<u8>) + Send + 'static,
+        F: FnMut(u8, &[u8]) + Send + 'static,
     {
-        eprintln!("Iniciando leitura USB do endpoint {:#04x}", endpoint);
+        eprintln!("Iniciando leitura USB dos endpoints {}", format_endpoints(endpoints));
 
-        let timeout = Duration::from_millis(500);
-        let mut max_packet_size = 0usize;
-        let mut iface_to_claim = None;
+        if endpoints.is_empty() {
+            bail!("Nenhum endpoint informado para leitura");
+        }
 
-        // Obtém descritores de configuração e busca o endpoint alvo.
+        // Obtém descritores de configuração e busca os endpoints alvo.
         let config_desc = device.active_config_descriptor()?;
-        'outer: for (i, interface) in config_desc.interfaces().enumerate() {
-            for (j, descriptor) in interface.descriptors().enumerate() {
-                println!("Interface {} Descriptor {}", i, j);
-                for ep in descriptor.endpoint_descriptors() {
-                    if endpoint == ep.address() {
-                        max_packet_size = ep.max_packet_size() as usize;
-                        iface_to_claim = Some(descriptor.interface_number());
-                        break 'outer;
-                    }
-                }
-            }
-        }
+        let mut targets = Vec::with_capacity(endpoints.len());
+        for &endpoint in endpoints {
+            let target = config_desc
+                .interfaces()
+                .flat_map(|interface| interface.descriptors())
+                .find_map(|descriptor| {
+                    descriptor
+                        .endpoint_descriptors()
+                        .find(|ep| ep.address() == endpoint)
+                        .map(|ep| EndpointTarget {
+                            address: endpoint,
+                            interface: descriptor.interface_number(),
+                            max_packet_size: ep.max_packet_size() as usize,
+                        })
+                })
+                .filter(|target| target.max_packet_size > 0);
 
-        if max_packet_size == 0 || iface_to_claim.is_none() {
-            bail!("Endpoint {:#04x} não encontrado", endpoint);
+            match target {
+                Some(target) => targets.push(target),
+                None => bail!(
+                    "Endpoint {:#04x} não encontrado. Candidatos:\n{}",
+                    endpoint,
+                    describe_candidates(&endpoint_candidates(&device)?)
+                ),
+            }
         }
 
-        let iface = iface_to_claim.unwrap();
         let handle = device.open()?;
+        handle.set_active_configuration(1).ok();
 
-        // Libera o driver do kernel, se ativo.
-        if handle.kernel_driver_active(iface)? {
-            handle.detach_kernel_driver(iface).ok();
-        }
+        // Reivindica cada interface uma única vez, liberando o driver do kernel
+        // se ativo e lembrando de reanexá-lo ao encerrar.
+        let mut interfaces: Vec<ClaimedInterface> = Vec::new();
+        for target in &targets {
+            let number = target.interface;
+            if interfaces.iter().any(|iface| iface.number == number) {
+                continue;
+            }
 
-        handle.set_active_configuration(1).ok();
-        handle.claim_interface(iface)?;
-
-        // Cria thread de leitura
-        thread::spawn(move || {
-            println!("🟢 Thread de leitura iniciada (endpoint {:#04x})", endpoint);
-            while stop_flag.load(Ordering::SeqCst) {
-                let mut buf = vec![0u8; max_packet_size];
-
-                match handle.read_interrupt(endpoint, &mut buf, timeout) {
-                    Ok(size) => {
-                        buf.truncate(size);
-                        callback(buf);
-                    }
-                    Err(UsbError::Timeout) => {
-                        // Tempo esgotado — loop continua
-                    }
-                    Err(e) => {
-                        eprintln!("⚠️ Erro na leitura USB: {:?}", e);
-                        break;
-                    }
+            let reattach_kernel_driver = handle.kernel_driver_active(number).unwrap_or(false)
+                && handle.detach_kernel_driver(number).is_ok();
+
+            if let Err(e) = handle.claim_interface(number) {
+                if reattach_kernel_driver {
+                    handle.attach_kernel_driver(number).ok();
                 }
+                release_interfaces(&handle, &interfaces);
+                bail!("Falha no claim da interface {}: {}", number, e);
             }
-            println!("🔴 Thread de leitura encerrada (endpoint {:#04x})", endpoint);
+
+            interfaces.push(ClaimedInterface {
+                number,
+                reattach_kernel_driver,
+            });
+        }
+
+        // Sequência de inicialização específica do dispositivo
+        if let Err(e) = run_init_sequence(&handle, targets[0].interface, init) {
+            release_interfaces(&handle, &interfaces);
+            return Err(e);
+        }
+
+        let shared = Arc::new(ReaderShared {
+            handle,
+            endpoints: endpoints.to_vec(),
+            interfaces,
+            callback: Mutex::new(Box::new(callback)),
+            stopping: AtomicBool::new(false),
+            errors: AtomicU32::new(0),
+            errors_total: AtomicU64::new(0),
+            cause: Mutex::new(None),
+            exit: Mutex::new(None),
+            in_flight: Mutex::new(Vec::with_capacity(TRANSFERS_IN_FLIGHT * targets.len())),
         });
 
-        Ok(())
+        submit_transfers(&shared, &targets)?;
+
+        // Acorda o loop de eventos para que as novas transferências sejam consideradas
+        shared.handle.context().interrupt_handle_events();
+
+        println!(
+            "🟢 Leitura iniciada (endpoints {}, {} transferências cada)",
+            format_endpoints(endpoints),
+            TRANSFERS_IN_FLIGHT
+        );
+
+        Ok(ReaderHandle { shared })
     }
 }
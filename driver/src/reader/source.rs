@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// Motivo do encerramento de uma fonte de pacotes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReaderExit {
+    /// Encerrada a pedido, via [`PacketSource::stop`].
+    Stopped,
+    /// O dispositivo foi removido.
+    Disconnected,
+    /// Erro fatal, ou erros transitórios além do limite de tentativas.
+    Failed { reason: String },
+}
+
+/// Fonte de pacotes HID em execução, independente do backend de leitura.
+///
+/// Cada backend ([`USBReader`](crate::reader::USBReader),
+/// [`HidrawReader`](crate::reader::HidrawReader)) entrega os pacotes a um
+/// *callback* `FnMut(u8, &[u8])` (endpoint de origem e pacote) e retorna uma implementação deste trait para
+/// controlar a leitura. Assim o tradutor não depende da origem dos pacotes.
+pub trait PacketSource: Send + Sync {
+    /// Encerra a leitura. O estado final fica disponível em
+    /// [`exit_status`](Self::exit_status) assim que os recursos forem liberados.
+    fn stop(&self);
+
+    /// Retorna `true` enquanto a leitura estiver ativa.
+    fn is_running(&self) -> bool;
+
+    /// Retorna o estado final, ou `None` se a leitura ainda não terminou.
+    fn exit_status(&self) -> Option<ReaderExit>;
+
+    /// Endpoints de origem dos pacotes (`0` se desconhecido).
+    fn endpoints(&self) -> &[u8];
+
+    /// Quantidade de erros de leitura desde o início (transitórios e fatais).
+    fn error_count(&self) -> u64;
+}
@@ -0,0 +1,2 @@
+pub mod session;
+pub use session::{DeviceKey, FinishedSession, SessionInfo, SessionManager, SessionState};
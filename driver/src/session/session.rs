@@ -0,0 +1,444 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use table_z_config::SourceBackend;
+
+use crate::reader::{PacketSource, ReaderExit};
+
+/// Identifica um dispositivo USB conectado pelo barramento e endereço.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DeviceKey {
+    pub bus: u8,
+    pub address: u8,
+}
+
+impl fmt::Display for DeviceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:03}:{:03}", self.bus, self.address)
+    }
+}
+
+/// Estado de uma sessão.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionState {
+    /// Leitor sendo iniciado (descoberta de endpoints, *claim*, inicialização)
+    Starting,
+    /// Leitor ativo
+    Running,
+    /// Encerramento solicitado; aguardando a liberação dos recursos
+    Stopping,
+}
+
+/// Resumo de uma sessão, enviado pelo socket em resposta ao comando `ListSessions`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub device: DeviceKey,
+    /// Caminho de portas USB (`1-2.3` → `[2, 3]`)
+    pub port_path: Vec<u8>,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub backend: SourceBackend,
+    pub state: SessionState,
+    /// Endpoints lidos (vazio enquanto a sessão é iniciada)
+    pub endpoints: Vec<u8>,
+    pub uptime_secs: u64,
+}
+
+/// Sessão de um dispositivo conectado.
+///
+/// O tradutor e os dispositivos virtuais pertencem ao *callback* do leitor,
+/// portanto cada sessão tem os seus, independentes das demais.
+struct Session {
+    port_path: Vec<u8>,
+    vendor_id: u16,
+    product_id: u16,
+    backend: SourceBackend,
+    started: Instant,
+    reader: Option<Box<dyn PacketSource>>,
+    stopping: bool,
+}
+
+/// Leitor encerrado, removido por [`SessionManager::reap_finished`].
+pub struct FinishedSession {
+    pub device: DeviceKey,
+    pub endpoints: Vec<u8>,
+    pub exit: ReaderExit,
+    pub errors: u64,
+}
+
+/// Gerenciador das sessões ativas, uma por dispositivo físico.
+///
+/// Permite vários tablets conectados ao mesmo tempo: cada um tem seu próprio
+/// leitor, tradutor e dispositivos virtuais, e a remoção de um dispositivo
+/// encerra apenas a sua sessão.
+#[derive(Default)]
+pub struct SessionManager {
+    sessions: Mutex<BTreeMap<DeviceKey, Session>>,
+}
+
+impl SessionManager {
+    /// Cria um gerenciador sem sessões.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra uma sessão em inicialização para o dispositivo.
+    ///
+    /// Retorna `false` se já houver uma sessão para `device`.
+    pub fn begin(
+        &self,
+        device: DeviceKey,
+        port_path: Vec<u8>,
+        vendor_id: u16,
+        product_id: u16,
+        backend: SourceBackend,
+    ) -> bool {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.contains_key(&device) {
+            return false;
+        }
+        sessions.insert(
+            device,
+            Session {
+                port_path,
+                vendor_id,
+                product_id,
+                backend,
+                started: Instant::now(),
+                reader: None,
+                stopping: false,
+            },
+        );
+        true
+    }
+
+    /// Associa o leitor iniciado à sessão do dispositivo.
+    ///
+    /// Se a sessão foi encerrada enquanto o leitor era iniciado (ex: o
+    /// dispositivo foi removido), o leitor é parado imediatamente.
+    pub fn attach(&self, device: DeviceKey, reader: Box<dyn PacketSource>) {
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get_mut(&device) {
+            Some(session) => {
+                if session.stopping {
+                    reader.stop();
+                }
+                session.reader = Some(reader);
+            }
+            None => reader.stop(),
+        }
+    }
+
+    /// Remove a sessão de um dispositivo cujo leitor não pôde ser iniciado.
+    pub fn abort(&self, device: DeviceKey) {
+        self.sessions.lock().unwrap().remove(&device);
+    }
+
+    /// Encerra a sessão do dispositivo.
+    ///
+    /// Retorna `false` se não houver sessão para `device`.
+    pub fn stop(&self, device: DeviceKey) -> bool {
+        let mut sessions = self.sessions.lock().unwrap();
+        let Some(session) = sessions.get_mut(&device) else {
+            return false;
+        };
+
+        // Ainda iniciando: o leitor será parado em `attach`
+        session.stopping = true;
+        if let Some(reader) = &session.reader {
+            reader.stop();
+        }
+        true
+    }
+
+    /// Encerra todas as sessões.
+    pub fn stop_all(&self) {
+        let mut sessions = self.sessions.lock().unwrap();
+        for session in sessions.values_mut() {
+            session.stopping = true;
+            if let Some(reader) = &session.reader {
+                reader.stop();
+            }
+        }
+    }
+
+    /// Remove e retorna as sessões cujos leitores já terminaram.
+    pub fn reap_finished(&self) -> Vec<FinishedSession> {
+        let mut sessions = self.sessions.lock().unwrap();
+
+        let finished: Vec<DeviceKey> = sessions
+            .iter()
+            .filter(|(_, s)| s.reader.as_ref().is_some_and(|r| r.exit_status().is_some()))
+            .map(|(key, _)| *key)
+            .collect();
+
+        finished
+            .into_iter()
+            .filter_map(|device| {
+                let reader = sessions.remove(&device)?.reader?;
+                Some(FinishedSession {
+                    device,
+                    endpoints: reader.endpoints().to_vec(),
+                    exit: reader.exit_status()?,
+                    errors: reader.error_count(),
+                })
+            })
+            .collect()
+    }
+
+    /// Aguarda até que todas as sessões tenham terminado, por no máximo `timeout`.
+    ///
+    /// Retorna `false` se o tempo se esgotar.
+    pub fn wait_finished(&self, timeout: Duration) -> bool {
+        let started = Instant::now();
+        loop {
+            self.reap_finished();
+            if self.is_empty() {
+                return true;
+            }
+            if started.elapsed() > timeout {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// Retorna `true` se não houver sessões.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.lock().unwrap().is_empty()
+    }
+
+    /// Soma dos erros de leitura das sessões ativas.
+    pub fn error_count(&self) -> u64 {
+        self.sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|s| s.reader.as_ref())
+            .map(|r| r.error_count())
+            .sum()
+    }
+
+    /// Lista as sessões ativas, ordenadas por dispositivo.
+    pub fn list(&self) -> Vec<SessionInfo> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(device, session)| SessionInfo {
+                device: *device,
+                port_path: session.port_path.clone(),
+                vendor_id: session.vendor_id,
+                product_id: session.product_id,
+                backend: session.backend,
+                state: match (&session.reader, session.stopping) {
+                    (_, true) => SessionState::Stopping,
+                    (None, false) => SessionState::Starting,
+                    (Some(_), false) => SessionState::Running,
+                },
+                endpoints: session
+                    .reader
+                    .as_ref()
+                    .map(|r| r.endpoints().to_vec())
+                    .unwrap_or_default(),
+                uptime_secs: session.started.elapsed().as_secs(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    };
+
+    /// Estado compartilhado entre o teste e a fonte de pacotes falsa.
+    #[derive(Default)]
+    struct FakeState {
+        stop_requested: AtomicBool,
+        exit: Mutex<Option<ReaderExit>>,
+    }
+
+    impl FakeState {
+        /// Simula o término da leitura.
+        fn finish(&self, exit: ReaderExit) {
+            *self.exit.lock().unwrap() = Some(exit);
+        }
+
+        fn stop_requested(&self) -> bool {
+            self.stop_requested.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Fonte de pacotes controlada pelo teste.
+    struct FakeSource {
+        state: Arc<FakeState>,
+        endpoints: Vec<u8>,
+        errors: u64,
+    }
+
+    impl PacketSource for FakeSource {
+        fn stop(&self) {
+            self.state.stop_requested.store(true, Ordering::SeqCst);
+        }
+
+        fn is_running(&self) -> bool {
+            self.exit_status().is_none()
+        }
+
+        fn exit_status(&self) -> Option<ReaderExit> {
+            self.state.exit.lock().unwrap().clone()
+        }
+
+        fn endpoints(&self) -> &[u8] {
+            &self.endpoints
+        }
+
+        fn error_count(&self) -> u64 {
+            self.errors
+        }
+    }
+
+    fn key(address: u8) -> DeviceKey {
+        DeviceKey { bus: 1, address }
+    }
+
+    fn begin(manager: &SessionManager, device: DeviceKey) -> bool {
+        manager.begin(device, vec![2, 3], 0x08f2, 0x6811, SourceBackend::Libusb)
+    }
+
+    /// Inicia a sessão e associa uma fonte falsa, retornando seu estado.
+    fn start(manager: &SessionManager, device: DeviceKey, errors: u64) -> Arc<FakeState> {
+        assert!(begin(manager, device));
+        let state = Arc::new(FakeState::default());
+        manager.attach(
+            device,
+            Box::new(FakeSource {
+                state: state.clone(),
+                endpoints: vec![0x81 + device.address],
+                errors,
+            }),
+        );
+        state
+    }
+
+    fn states(manager: &SessionManager) -> Vec<(DeviceKey, SessionState)> {
+        manager.list().iter().map(|s| (s.device, s.state)).collect()
+    }
+
+    #[test]
+    fn begin_rejects_duplicate_device() {
+        let manager = SessionManager::new();
+
+        assert!(begin(&manager, key(7)));
+        assert!(!begin(&manager, key(7)));
+        assert!(begin(&manager, key(8)));
+        assert_eq!(manager.list().len(), 2);
+
+        manager.abort(key(7));
+        assert!(begin(&manager, key(7)));
+    }
+
+    #[test]
+    fn stop_requests_the_reader_to_stop() {
+        let manager = SessionManager::new();
+        let reader = start(&manager, key(7), 0);
+        let other = start(&manager, key(8), 0);
+
+        assert!(manager.stop(key(7)));
+        assert!(!manager.stop(key(9)));
+
+        assert!(reader.stop_requested());
+        assert!(!other.stop_requested());
+        assert_eq!(
+            states(&manager),
+            vec![(key(7), SessionState::Stopping), (key(8), SessionState::Running)]
+        );
+    }
+
+    #[test]
+    fn reader_attached_after_stop_is_stopped() {
+        let manager = SessionManager::new();
+        assert!(begin(&manager, key(7)));
+        assert!(manager.stop(key(7)));
+
+        let state = Arc::new(FakeState::default());
+        let source = FakeSource { state: state.clone(), endpoints: vec![], errors: 0 };
+        manager.attach(key(7), Box::new(source));
+        assert!(state.stop_requested());
+
+        // Sessão já removida: o leitor também é parado
+        let orphan = Arc::new(FakeState::default());
+        let source = FakeSource { state: orphan.clone(), endpoints: vec![], errors: 0 };
+        manager.attach(key(9), Box::new(source));
+        assert!(orphan.stop_requested());
+    }
+
+    #[test]
+    fn stop_all_stops_every_session() {
+        let manager = SessionManager::new();
+        let readers = [start(&manager, key(7), 0), start(&manager, key(8), 0)];
+        assert!(begin(&manager, key(9)));
+
+        manager.stop_all();
+
+        assert!(readers.iter().all(|r| r.stop_requested()));
+        assert!(states(&manager).iter().all(|(_, s)| *s == SessionState::Stopping));
+    }
+
+    #[test]
+    fn reap_removes_only_finished_sessions() {
+        let manager = SessionManager::new();
+        let first = start(&manager, key(7), 3);
+        let second = start(&manager, key(8), 0);
+        assert!(begin(&manager, key(9)));
+
+        assert!(manager.reap_finished().is_empty());
+
+        first.finish(ReaderExit::Disconnected);
+        let finished = manager.reap_finished();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].device, key(7));
+        assert_eq!(finished[0].endpoints, vec![0x88]);
+        assert_eq!(finished[0].exit, ReaderExit::Disconnected);
+        assert_eq!(finished[0].errors, 3);
+
+        // Sessões ainda em inicialização não são removidas
+        manager.abort(key(9));
+        assert!(!manager.wait_finished(Duration::from_millis(50)));
+
+        second.finish(ReaderExit::Stopped);
+        assert!(manager.wait_finished(Duration::from_millis(50)));
+        assert!(manager.is_empty());
+    }
+
+    #[test]
+    fn list_reports_state_endpoints_and_errors() {
+        let manager = SessionManager::new();
+        start(&manager, key(8), 2);
+        assert!(begin(&manager, key(7)));
+        start(&manager, key(9), 5);
+
+        let sessions = manager.list();
+        assert_eq!(
+            states(&manager),
+            vec![
+                (key(7), SessionState::Starting),
+                (key(8), SessionState::Running),
+                (key(9), SessionState::Running),
+            ]
+        );
+        assert!(sessions[0].endpoints.is_empty());
+        assert_eq!(sessions[1].endpoints, vec![0x89]);
+        assert_eq!(sessions[1].port_path, vec![2, 3]);
+        assert_eq!((sessions[1].vendor_id, sessions[1].product_id), (0x08f2, 0x6811));
+        assert_eq!(sessions[1].backend, SourceBackend::Libusb);
+        assert_eq!(manager.error_count(), 7);
+    }
+}
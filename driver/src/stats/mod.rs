@@ -0,0 +1,2 @@
+pub mod stats;
+pub use stats::{InputStats, ReportStats, StatsSnapshot};
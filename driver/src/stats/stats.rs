@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{
+    Mutex,
+    atomic::{AtomicU64, Ordering},
+};
+use std::time::{Duration, Instant};
+
+/// Quantidade de report IDs possíveis (primeiro byte do pacote).
+const REPORT_IDS: usize = 256;
+
+/// Janela usada no cálculo das taxas de pacotes por segundo.
+struct RateWindow {
+    /// Instante do último cálculo
+    at: Instant,
+    /// Contagem por report ID no último cálculo
+    counts: Vec<u64>,
+    /// Pacotes por segundo por report ID, calculados no último intervalo
+    rates: Vec<f64>,
+}
+
+/// Contadores de entrada do driver.
+///
+/// Registra a quantidade de pacotes por tipo de relatório (report ID), falhas
+/// de decodificação e o tempo de processamento de cada pacote. Os contadores
+/// são atômicos e atualizados no caminho de leitura sem bloqueio; as taxas
+/// (pacotes/s) são recalculadas a cada chamada de [`tick`](Self::tick).
+///
+/// Erros de leitura USB e mensagens descartadas pelo socket são mantidos
+/// pelos próprios componentes e informados em [`snapshot`](Self::snapshot).
+pub struct InputStats {
+    started: Instant,
+    packets: Vec<AtomicU64>,
+    decode_failures: AtomicU64,
+    processing_ns_total: AtomicU64,
+    processing_ns_max: AtomicU64,
+    /// Erros de leitores já encerrados
+    past_usb_errors: AtomicU64,
+    window: Mutex<RateWindow>,
+}
+
+/// Estatísticas de um tipo de relatório.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportStats {
+    /// Report ID (primeiro byte do pacote)
+    pub report_id: u8,
+    /// Pacotes recebidos desde o início
+    pub packets: u64,
+    /// Pacotes por segundo no último intervalo
+    pub rate_hz: f64,
+}
+
+/// Fotografia das estatísticas, enviada pelo socket em resposta ao comando `Status`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    /// Tempo desde o início do driver, em segundos
+    pub uptime_secs: u64,
+    /// Total de pacotes recebidos
+    pub packets: u64,
+    /// Estatísticas por report ID (apenas os já recebidos)
+    pub reports: Vec<ReportStats>,
+    /// Pacotes que o tradutor não reconheceu
+    pub decode_failures: u64,
+    /// Erros de leitura USB (transitórios e fatais)
+    pub usb_errors: u64,
+    /// Mensagens que não puderam ser entregues aos clientes do socket
+    pub dropped_socket_messages: u64,
+    /// Tempo médio de processamento por pacote, em microssegundos
+    pub processing_avg_us: f64,
+    /// Maior tempo de processamento de um pacote, em microssegundos
+    pub processing_max_us: f64,
+}
+
+impl fmt::Display for StatsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rates = self
+            .reports
+            .iter()
+            .map(|r| format!("{:#04x}: {:.0} Hz", r.report_id, r.rate_hz))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            f,
+            "{} pacotes [{}] | falhas de decodificação: {} | erros USB: {} | \
+             mensagens descartadas: {} | processamento: {:.1} µs (máx {:.1} µs)",
+            self.packets,
+            rates,
+            self.decode_failures,
+            self.usb_errors,
+            self.dropped_socket_messages,
+            self.processing_avg_us,
+            self.processing_max_us
+        )
+    }
+}
+
+impl Default for InputStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputStats {
+    /// Cria os contadores zerados.
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            packets: (0..REPORT_IDS).map(|_| AtomicU64::new(0)).collect(),
+            decode_failures: AtomicU64::new(0),
+            processing_ns_total: AtomicU64::new(0),
+            processing_ns_max: AtomicU64::new(0),
+            past_usb_errors: AtomicU64::new(0),
+            window: Mutex::new(RateWindow {
+                at: now,
+                counts: vec![0; REPORT_IDS],
+                rates: vec![0.0; REPORT_IDS],
+            }),
+        }
+    }
+
+    /// Registra um pacote processado.
+    ///
+    /// - `recognized`: retorno de [`Translator::conv`](crate::translator::translator::Translator::conv).
+    /// - `elapsed`: tempo gasto na tradução e emissão do pacote.
+    pub fn record_packet(&self, buf: &[u8], recognized: bool, elapsed: Duration) {
+        let report_id = buf.first().copied().unwrap_or(0) as usize;
+        self.packets[report_id].fetch_add(1, Ordering::Relaxed);
+
+        if !recognized {
+            self.decode_failures.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let ns = elapsed.as_nanos().min(u64::MAX as u128) as u64;
+        self.processing_ns_total.fetch_add(ns, Ordering::Relaxed);
+        self.processing_ns_max.fetch_max(ns, Ordering::Relaxed);
+    }
+
+    /// Acumula os erros de um leitor encerrado, para que não se percam.
+    pub fn add_usb_errors(&self, errors: u64) {
+        self.past_usb_errors.fetch_add(errors, Ordering::Relaxed);
+    }
+
+    /// Recalcula as taxas de pacotes por segundo desde a última chamada.
+    ///
+    /// Deve ser chamado periodicamente (ex: a cada segundo pelo loop principal).
+    pub fn tick(&self) {
+        let mut window = self.window.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(window.at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return;
+        }
+
+        let RateWindow { counts, rates, .. } = &mut *window;
+        for (id, counter) in self.packets.iter().enumerate() {
+            let count = counter.load(Ordering::Relaxed);
+            rates[id] = (count - counts[id]) as f64 / elapsed;
+            counts[id] = count;
+        }
+        window.at = now;
+    }
+
+    /// Gera uma fotografia das estatísticas.
+    ///
+    /// - `usb_errors`: erros do leitor ativo, somados aos de leitores encerrados.
+    /// - `dropped_socket_messages`: mensagens descartadas pelo socket.
+    pub fn snapshot(&self, usb_errors: u64, dropped_socket_messages: u64) -> StatsSnapshot {
+        let window = self.window.lock().unwrap();
+
+        let reports: Vec<ReportStats> = self
+            .packets
+            .iter()
+            .enumerate()
+            .filter_map(|(id, counter)| {
+                let packets = counter.load(Ordering::Relaxed);
+                (packets > 0).then(|| ReportStats {
+                    report_id: id as u8,
+                    packets,
+                    rate_hz: window.rates[id],
+                })
+            })
+            .collect();
+
+        let packets: u64 = reports.iter().map(|r| r.packets).sum();
+        let total_ns = self.processing_ns_total.load(Ordering::Relaxed);
+
+        StatsSnapshot {
+            uptime_secs: self.started.elapsed().as_secs(),
+            packets,
+            reports,
+            decode_failures: self.decode_failures.load(Ordering::Relaxed),
+            usb_errors: self.past_usb_errors.load(Ordering::Relaxed) + usb_errors,
+            dropped_socket_messages,
+            processing_avg_us: if packets > 0 {
+                total_ns as f64 / packets as f64 / 1000.0
+            } else {
+                0.0
+            },
+            processing_max_us: self.processing_ns_max.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn us(micros: u64) -> Duration {
+        Duration::from_micros(micros)
+    }
+
+    /// Recua o início da janela de taxas, simulando `secs` segundos decorridos.
+    fn age_window(stats: &InputStats, secs: u64) {
+        let mut window = stats.window.lock().unwrap();
+        window.at = Instant::now().checked_sub(Duration::from_secs(secs)).unwrap();
+    }
+
+    #[test]
+    fn counts_packets_per_report_id() {
+        let stats = InputStats::new();
+        stats.record_packet(&[2, 1, 0, 86], true, us(1));
+        stats.record_packet(&[2, 0, 0, 0], true, us(1));
+        stats.record_packet(&[0x0a, 193], true, us(1));
+        stats.record_packet(&[], false, us(1));
+
+        let snapshot = stats.snapshot(0, 0);
+        let counts: Vec<(u8, u64)> =
+            snapshot.reports.iter().map(|r| (r.report_id, r.packets)).collect();
+        assert_eq!(counts, vec![(0x00, 1), (0x02, 2), (0x0a, 1)]);
+        assert_eq!(snapshot.packets, 4);
+    }
+
+    #[test]
+    fn counts_decode_failures() {
+        let stats = InputStats::new();
+        stats.record_packet(&[2, 1], true, us(1));
+        stats.record_packet(&[7, 1], false, us(1));
+        stats.record_packet(&[7, 2], false, us(1));
+
+        let snapshot = stats.snapshot(0, 0);
+        assert_eq!(snapshot.decode_failures, 2);
+        assert_eq!(snapshot.packets, 3);
+    }
+
+    #[test]
+    fn reports_usb_errors_and_dropped_socket_messages() {
+        let stats = InputStats::new();
+        stats.add_usb_errors(3);
+        stats.add_usb_errors(2);
+
+        let snapshot = stats.snapshot(4, 9);
+        assert_eq!(snapshot.usb_errors, 9);
+        assert_eq!(snapshot.dropped_socket_messages, 9);
+    }
+
+    #[test]
+    fn processing_time_average_and_max() {
+        let stats = InputStats::new();
+        assert_eq!(stats.snapshot(0, 0).processing_avg_us, 0.0);
+
+        stats.record_packet(&[2], true, us(10));
+        stats.record_packet(&[2], true, us(30));
+        stats.record_packet(&[3], true, us(50));
+
+        let snapshot = stats.snapshot(0, 0);
+        assert_eq!(snapshot.processing_avg_us, 30.0);
+        assert_eq!(snapshot.processing_max_us, 50.0);
+    }
+
+    #[test]
+    fn tick_computes_rates_since_last_tick() {
+        let stats = InputStats::new();
+        for _ in 0..100 {
+            stats.record_packet(&[2], true, us(1));
+        }
+        stats.record_packet(&[3], true, us(1));
+
+        // Antes do primeiro cálculo as taxas são zero
+        assert!(stats.snapshot(0, 0).reports.iter().all(|r| r.rate_hz == 0.0));
+
+        age_window(&stats, 2);
+        stats.tick();
+        let rates: Vec<f64> = stats.snapshot(0, 0).reports.iter().map(|r| r.rate_hz).collect();
+        assert!((rates[0] - 50.0).abs() < 0.1, "{rates:?}");
+        assert!((rates[1] - 0.5).abs() < 0.01, "{rates:?}");
+
+        // Apenas os pacotes do novo intervalo entram na taxa
+        for _ in 0..10 {
+            stats.record_packet(&[2], true, us(1));
+        }
+        age_window(&stats, 1);
+        stats.tick();
+        let snapshot = stats.snapshot(0, 0);
+        assert_eq!(snapshot.reports[0].packets, 110);
+        assert!((snapshot.reports[0].rate_hz - 10.0).abs() < 0.1);
+        assert_eq!(snapshot.reports[1].rate_hz, 0.0);
+    }
+}
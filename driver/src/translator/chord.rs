@@ -0,0 +1,105 @@
+use evdev::Key;
+
+use crate::translator::translator::EmitCommand;
+
+/// Quantidade de códigos de tecla rastreados (`KEY_MAX + 1`).
+const KEY_COUNT: usize = 0x300;
+
+/// Retorna `true` se a tecla for um modificador (Ctrl, Shift, Alt ou Meta).
+pub fn is_modifier(key: Key) -> bool {
+    matches!(
+        key,
+        Key::KEY_LEFTCTRL
+            | Key::KEY_RIGHTCTRL
+            | Key::KEY_LEFTSHIFT
+            | Key::KEY_RIGHTSHIFT
+            | Key::KEY_LEFTALT
+            | Key::KEY_RIGHTALT
+            | Key::KEY_LEFTMETA
+            | Key::KEY_RIGHTMETA
+    )
+}
+
+/// Estado das combinações de teclas (chords) mantidas pelos botões físicos.
+///
+/// Garante que:
+/// - Modificadores são pressionados antes das demais teclas da combinação;
+/// - A liberação ocorre na ordem inversa do pressionamento;
+/// - Teclas compartilhadas por botões pressionados simultaneamente são
+///   contadas por referência, e só são liberadas quando o último botão que
+///   as utiliza for solto.
+///
+/// Cada botão ocupa um *slot* fixo, que guarda as teclas efetivamente
+/// pressionadas; assim a liberação não depende da configuração vigente.
+pub struct ChordState {
+    /// Quantidade de botões segurando cada código de tecla
+    refs: [u8; KEY_COUNT],
+    /// Teclas pressionadas por slot, na ordem de pressionamento
+    held: Vec<Vec<Key>>,
+}
+
+impl ChordState {
+    /// Cria o estado para `slots` botões.
+    pub fn new(slots: usize) -> Self {
+        Self {
+            refs: [0; KEY_COUNT],
+            held: (0..slots).map(|_| Vec::with_capacity(4)).collect(),
+        }
+    }
+
+    /// Pressiona a combinação `keys` no slot informado.
+    ///
+    /// Emite em `out` apenas as teclas que ainda não estavam pressionadas
+    /// por outro botão. Não faz nada se o slot já estiver pressionado.
+    pub fn press(&mut self, slot: usize, keys: &[Key], index: usize, out: &mut Vec<EmitCommand>) {
+        let Some(held) = self.held.get_mut(slot) else {
+            return;
+        };
+        if !held.is_empty() {
+            return;
+        }
+
+        // Modificadores primeiro, preservando a ordem configurada
+        held.extend(keys.iter().copied().filter(|k| is_modifier(*k)));
+        held.extend(keys.iter().copied().filter(|k| !is_modifier(*k)));
+
+        for key in held.iter() {
+            let Some(count) = self.refs.get_mut(key.code() as usize) else {
+                continue;
+            };
+            *count = count.saturating_add(1);
+            if *count == 1 {
+                out.push(EmitCommand::Btn {
+                    key: key.code() as i32,
+                    pressed: true,
+                    index,
+                });
+            }
+        }
+    }
+
+    /// Libera a combinação mantida no slot informado, na ordem inversa do pressionamento.
+    ///
+    /// Emite em `out` apenas as teclas que não são mais usadas por nenhum botão.
+    pub fn release(&mut self, slot: usize, index: usize, out: &mut Vec<EmitCommand>) {
+        let Some(held) = self.held.get_mut(slot) else {
+            return;
+        };
+
+        for key in held.iter().rev() {
+            let Some(count) = self.refs.get_mut(key.code() as usize) else {
+                continue;
+            };
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                out.push(EmitCommand::Btn {
+                    key: key.code() as i32,
+                    pressed: false,
+                    index,
+                });
+            }
+        }
+
+        held.clear();
+    }
+}
@@ -1,2 +1,3 @@
 pub mod translator;
 pub mod tablet_m100_translator;
+pub mod chord;
@@ -1,138 +1,455 @@
-use std::collections::HashSet;
-use std::{
-    str::FromStr,
-    sync::{Arc, Mutex},
-};
+use std::str::FromStr;
 
+use anyhow::{Context, Result};
 use evdev::Key;
-use crate::translator::translator::{EmitCommand, Translator};
-use table_z_config::Config;
+use crate::translator::{
+    chord::ChordState,
+    translator::{ConfigSnapshot, EmitCommand, Translator},
+};
+use crate::virtual_device::{VPad, WHEEL_DETENT};
+use table_z_config::{ButtonMapConfig, Config};
 
-/// Tradutor responsável por interpretar os pacotes de dados de um tablet modelo M100
-/// e convertê-los em comandos lógicos de entrada (`EmitCommand`).
+/// Mapeamento estático dos botões do dispositivo: `(buf[1], buf[3], índice)`,
+/// em pacotes com `buf[0] == BUTTON_REPORT_ID`.
 ///
-/// Este componente é responsável por:
-/// - Interpretar pacotes USB do tablet
-/// - Converter valores brutos de coordenadas e pressão em eventos de caneta
-/// - Mapear botões físicos do tablet para combinações configuráveis de teclas
-/// - Gerenciar o estado de teclas pressionadas para emitir eventos corretos
-pub struct TabletM100Translator {
+/// Índices a partir de `5000` representam os botões da caneta; os demais
+/// indexam `action_tablet_buttons`. Os botões do tablet podem ser substituídos
+/// pela tabela `button_map` da configuração.
+const BUTTON_MAPPING: [(u8, u8, usize); 10] = [
+    (1, 28, 5000), // BTN_STYLUS
+    (1, 29, 5001), // BTN_STYLUS2
+    (1, 86, 0),    // Botão 1
+    (1, 87, 1),    // Botão 2
+    (0, 47, 2),    // Botão 3
+    (0, 48, 3),    // Botão 4
+    (0, 43, 4),    // Botão 5
+    (0, 44, 5),    // Botão 6
+    (1, 0, 6),     // Botão 7
+    (4, 0, 7),     // Botão 8
+];
+
+/// ID do relatório dos pacotes de botões do M100.
+const BUTTON_REPORT_ID: u8 = 2;
+
+/// Quantidade máxima de botões rastreados (bits de `pressed_buttons`).
+const MAX_BUTTONS: usize = 32;
+
+/// Índice lógico usado nos eventos da ação `pen_touch`.
+const PEN_TOUCH_INDEX: usize = 5002;
+
+/// Estado da caneta (`buf[1]`) reportado quando ela sai do alcance da mesa
+/// (bit de proximidade `0x40` desligado).
+const PEN_OUT_OF_RANGE: u8 = 0x80;
+
+/// Assinatura de um botão físico nos pacotes HID.
+#[derive(Debug, Clone)]
+pub struct ButtonSignature {
+    /// ID do relatório (`buf[0]`) em que o botão é reportado
+    pub report_id: u8,
+    /// Pares `(posição, valor)` que identificam o botão pressionado
+    pub bytes: Vec<(usize, u8)>,
+    /// Índice lógico do botão (`>= 5000` para botões da caneta)
+    pub index: usize,
+}
+
+impl ButtonSignature {
+    /// Retorna `true` se o pacote corresponder a este botão.
+    fn matches(&self, buf: &[u8]) -> bool {
+        buf[0] == self.report_id
+            && self.bytes.iter().all(|(offset, value)| buf.get(*offset) == Some(value))
+    }
+}
+
+/// Monta as assinaturas dos botões: os da caneta sempre vêm do mapeamento
+/// embutido; os do tablet vêm de `button_map`, quando configurado.
+fn button_signatures(button_map: Option<&ButtonMapConfig>) -> Result<Vec<ButtonSignature>> {
+    let builtin = BUTTON_MAPPING.iter().map(|(b1, b3, idx)| ButtonSignature {
+        report_id: BUTTON_REPORT_ID,
+        bytes: vec![(1, *b1), (3, *b3)],
+        index: *idx,
+    });
+
+    let Some(map) = button_map else {
+        return Ok(builtin.collect());
+    };
+
+    let mut signatures: Vec<ButtonSignature> = builtin.filter(|s| s.index >= 5000).collect();
+    for (i, values) in map.buttons.iter().enumerate() {
+        if values.len() != map.offsets.len() {
+            anyhow::bail!(
+                "button_map: botão {} tem {} valores, esperado {}",
+                i + 1,
+                values.len(),
+                map.offsets.len()
+            );
+        }
+        signatures.push(ButtonSignature {
+            report_id: map.report_id,
+            bytes: map.offsets.iter().copied().zip(values.iter().copied()).collect(),
+            index: i,
+        });
+    }
+
+    if signatures.len() > MAX_BUTTONS {
+        anyhow::bail!("button_map: no máximo {} botões são suportados", MAX_BUTTONS - 2);
+    }
+
+    Ok(signatures)
+}
+
+/// Configuração interpretada do tradutor M100.
+///
+/// Produzida a partir de uma [`Config`] por [`Translator::settings_from_config`]
+/// e publicada como snapshot imutável para a thread de leitura.
+#[derive(Debug, Clone)]
+pub struct TabletM100Settings {
     // --- Propriedades do hardware ---
     /// Valor máximo do eixo X
-    pen_max_x: u32,
+    pub pen_max_x: u32,
     /// Valor máximo do eixo Y
-    pen_max_y: u32,
+    pub pen_max_y: u32,
     /// Pressão máxima reconhecida pela caneta
-    pen_max_pressure: u32,
-    /// Resolução em DPI do eixo X
-    pen_resolution_x: u32,
-    /// Resolução em DPI do eixo Y
-    pen_resolution_y: u32,
+    pub pen_max_pressure: u32,
+    /// Resolução do eixo X, em unidades/mm
+    pub pen_resolution_x: u32,
+    /// Resolução do eixo Y, em unidades/mm
+    pub pen_resolution_y: u32,
 
     // --- Ações configuráveis ---
     /// Tecla associada ao clique da caneta
-    action_pen: Key,
+    pub action_pen: Option<Key>,
     /// Tecla associada ao botão lateral (stylus)
-    action_stylus: Key,
+    pub action_stylus: Option<Key>,
     /// Tecla associada ao toque da caneta na superfície
-    action_pen_touch: Key,
-    /// Lista de combinações de teclas para os botões físicos do tablet
-    pub action_tablet_buttons: Vec<Vec<Key>>,
+    pub action_pen_touch: Option<Key>,
+    /// Ações dos botões físicos do tablet
+    pub action_tablet_buttons: Vec<ButtonAction>,
+    /// Assinaturas dos botões físicos (caneta e tablet)
+    pub buttons: Vec<ButtonSignature>,
 
     // --- Flags de transformação ---
     /// Inverte eixos X e Y
-    swap_axis: bool,
+    pub swap_axis: bool,
     /// Inverte a direção do eixo X
-    swap_direction_x: bool,
+    pub swap_direction_x: bool,
     /// Inverte a direção do eixo Y
-    swap_direction_y: bool,
+    pub swap_direction_y: bool,
 
-    /// Conjunto de índices de botões atualmente pressionados
-    pressed_keys: Mutex<HashSet<usize>>,
+    // --- Toque da ponta ---
+    /// Pressão mínima para ativar o toque (`0` usa o indicador do hardware)
+    pub touch_threshold: u32,
+    /// Histerese aplicada na liberação do toque
+    pub touch_hysteresis: u32,
 }
 
-impl TabletM100Translator {
-    /// Cria uma nova instância do tradutor a partir de uma configuração compartilhada (`Arc<Mutex<Config>>`)
-    pub fn new(cfg: Arc<Mutex<Config>>) -> Self {
-        let cfg_guard = cfg.lock().unwrap();
-
-        // --- Conversão de strings para `Key` ---
-        let action_pen = Key::from_str(&cfg_guard.actions.pen)
-            .expect("Chave inválida para pen");
-        let action_stylus = Key::from_str(&cfg_guard.actions.stylus)
-            .expect("Chave inválida para stylus");
-        let action_pen_touch = Key::from_str(&cfg_guard.actions.pen_touch)
-            .expect("Chave inválida para pen_touch");
-
-        // Converte botões configurados como combinações (ex: "Ctrl+Z")
-        let action_tablet_buttons = cfg_guard
-            .actions
-            .tablet_buttons
-            .iter()
-            .map(|combo_str| {
-                combo_str
-                    .split('+')
-                    .map(|s| Key::from_str(s.trim()).expect("Chave inválida"))
-                    .collect::<Vec<Key>>()
-            })
-            .collect::<Vec<Vec<Key>>>();
+/// Converte o nome de uma tecla (ex: `"KEY_A"`) em [`Key`].
+///
+/// Nomes vazios ou `"None"` (usados pela UI para ações não mapeadas) resultam em `None`.
+fn parse_key(name: &str) -> Result<Option<Key>> {
+    let name = name.trim();
+    if name.is_empty() || name == "None" {
+        return Ok(None);
+    }
+
+    Key::from_str(name)
+        .map(Some)
+        .map_err(|_| anyhow::anyhow!("Chave inválida: {:?}", name))
+}
 
-        println!("action_tablet_buttons: {:?}", action_tablet_buttons);
+/// Ação associada a um botão físico do tablet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ButtonAction {
+    /// Combinação de teclas (ex: `KEY_LEFTCTRL+KEY_Z`)
+    Keys(Vec<Key>),
+    /// Alterna para a borracha enquanto o botão estiver pressionado (`ERASER`)
+    Eraser,
+    /// Alterna entre caneta e borracha a cada pressionamento (`ERASER_TOGGLE`)
+    EraserToggle,
+    /// Botão do dispositivo de pad (`PAD`), na posição do botão físico
+    Pad(Key),
+    /// Um passo de rolagem do mouse virtual a cada pressionamento
+    /// (`SCROLL_UP`/`SCROLL_DOWN`/`SCROLL_LEFT`/`SCROLL_RIGHT`), com `modifier`
+    /// mantido enquanto o botão estiver pressionado (`ZOOM_IN`/`ZOOM_OUT` usam `Ctrl`)
+    Scroll {
+        modifier: Option<Key>,
+        horizontal: bool,
+        amount: i32,
+    },
+}
+
+impl ButtonAction {
+    /// Interpreta a ação configurada para o botão `index`: uma das palavras-chave
+    /// (`ERASER`, `PAD`, `SCROLL_UP`, `ZOOM_IN`, ...) ou uma combinação de teclas
+    /// separadas por `+`. Os botões de mouse (`BTN_LEFT`, `BTN_RIGHT`,
+    /// `BTN_MIDDLE`) são teclas comuns, emitidas pelo mouse virtual.
+    fn parse(combo: &str, index: usize) -> Result<Self> {
+        let scroll = |modifier, horizontal, amount| {
+            Ok(Self::Scroll { modifier, horizontal, amount })
+        };
+        match combo.trim() {
+            "SCROLL_UP" => scroll(None, false, WHEEL_DETENT),
+            "SCROLL_DOWN" => scroll(None, false, -WHEEL_DETENT),
+            "SCROLL_LEFT" => scroll(None, true, -WHEEL_DETENT),
+            "SCROLL_RIGHT" => scroll(None, true, WHEEL_DETENT),
+            "ZOOM_IN" => scroll(Some(Key::KEY_LEFTCTRL), false, WHEEL_DETENT),
+            "ZOOM_OUT" => scroll(Some(Key::KEY_LEFTCTRL), false, -WHEEL_DETENT),
+            "ERASER" => Ok(Self::Eraser),
+            "ERASER_TOGGLE" => Ok(Self::EraserToggle),
+            "PAD" => VPad::button_key(index).map(Self::Pad).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "PAD: apenas os {} primeiros botões são suportados",
+                    VPad::MAX_BUTTONS
+                )
+            }),
+            _ => combo
+                .split('+')
+                .filter_map(|k| parse_key(k).transpose())
+                .collect::<Result<Vec<Key>>>()
+                .map(Self::Keys),
+        }
+    }
+}
+
+/// Estado da ferramenta de borracha.
+#[derive(Default)]
+struct EraserState {
+    /// Máscara de bits dos botões que seguram a borracha momentânea
+    held: u32,
+    /// Borracha ativada pela ação de alternância
+    toggled: bool,
+    /// Última ferramenta sinalizada (`true` = borracha)
+    active: bool,
+}
 
+impl EraserState {
+    /// Sinaliza a troca de ferramenta, se houver.
+    ///
+    /// A ação momentânea inverte a ferramenta escolhida pela alternância, de
+    /// modo que segurar o botão com a borracha alternada volta para a caneta.
+    fn update(&mut self, out: &mut Vec<EmitCommand>) {
+        let active = (self.held != 0) != self.toggled;
+        if active != self.active {
+            self.active = active;
+            out.push(EmitCommand::Tool { eraser: active });
+        }
+    }
+}
+
+/// Tradutor responsável por interpretar os pacotes de dados de um tablet modelo M100
+/// e convertê-los em comandos lógicos de entrada (`EmitCommand`).
+///
+/// Este componente é responsável por:
+/// - Interpretar pacotes USB do tablet
+/// - Converter valores brutos de coordenadas e pressão em eventos de caneta
+/// - Mapear botões físicos do tablet para combinações configuráveis de teclas
+/// - Gerenciar o estado de teclas pressionadas para emitir eventos corretos
+///
+/// A configuração é lida de um [`ConfigSnapshot`] compartilhado; o estado dos
+/// botões pertence exclusivamente a esta instância (uma por dispositivo).
+pub struct TabletM100Translator {
+    /// Snapshot atual da configuração, atualizado atomicamente
+    settings: ConfigSnapshot<TabletM100Settings>,
+
+    /// Máscara de bits das assinaturas de botões atualmente pressionadas
+    pressed_buttons: u32,
+
+    /// Combinações de teclas mantidas pelos botões pressionados (um slot por assinatura)
+    chords: ChordState,
+
+    /// Indica se a ponta da caneta está em contato (após limiar e histerese)
+    touching: bool,
+
+    /// Indica se a caneta está no alcance da mesa (proximidade)
+    in_range: bool,
+
+    /// Ferramenta selecionada pelas ações de borracha
+    eraser: EraserState,
+}
+
+impl TabletM100Translator {
+    /// Cria uma nova instância do tradutor lendo a configuração do snapshot compartilhado.
+    pub fn new(settings: ConfigSnapshot<TabletM100Settings>) -> Self {
         Self {
-            pen_max_x: cfg_guard.pen.max_x,
-            pen_max_y: cfg_guard.pen.max_y,
-            pen_max_pressure: cfg_guard.pen.max_pressure,
-            pen_resolution_x: cfg_guard.pen.resolution_x,
-            pen_resolution_y: cfg_guard.pen.resolution_y,
-            action_pen,
-            action_stylus,
-            action_pen_touch,
-            action_tablet_buttons,
-            swap_axis: cfg_guard.settings.swap_axis,
-            swap_direction_x: cfg_guard.settings.swap_direction_x,
-            swap_direction_y: cfg_guard.settings.swap_direction_y,
-            pressed_keys: Mutex::new(HashSet::new()),
+            settings,
+            pressed_buttons: 0,
+            chords: ChordState::new(MAX_BUTTONS),
+            touching: false,
+            in_range: false,
+            eraser: EraserState::default(),
+        }
+    }
+
+    /// Decide se a ponta está em contato.
+    ///
+    /// Com `touch_threshold == 0`, usa o indicador de contato do hardware (`hw_touch`).
+    /// Caso contrário, o toque é ativado quando a pressão atinge o limiar e só é
+    /// liberado quando cai abaixo de `touch_threshold - touch_hysteresis`
+    /// (ou chega a zero), independentemente do tipo de pacote.
+    fn touch_state(&self, settings: &TabletM100Settings, hw_touch: bool, pressure: i32) -> bool {
+        if settings.touch_threshold == 0 {
+            return hw_touch;
+        }
+
+        let pressure = pressure.max(0) as u32;
+        if self.touching {
+            let release_level = settings
+                .touch_threshold
+                .saturating_sub(settings.touch_hysteresis)
+                .max(1);
+            pressure >= release_level
+        } else {
+            pressure >= settings.touch_threshold
+        }
+    }
+
+    /// Atualiza o estado de toque, disparando a ação configurada nas transições
+    /// (`BTN_TOUCH` já é emitido pela própria caneta).
+    fn set_touching(&mut self, settings: &TabletM100Settings, touch: bool, out: &mut Vec<EmitCommand>) {
+        if touch == self.touching {
+            return;
+        }
+        self.touching = touch;
+
+        if let Some(key) = settings.action_pen_touch
+            && key != Key::BTN_TOUCH
+        {
+            out.push(EmitCommand::Btn {
+                key: key.code() as i32,
+                pressed: touch,
+                index: PEN_TOUCH_INDEX,
+            });
+        }
+    }
+
+    /// Pressiona as combinações de todos os botões presentes em `mask`.
+    fn press_buttons(
+        chords: &mut ChordState,
+        eraser: &mut EraserState,
+        settings: &TabletM100Settings,
+        mask: u32,
+        out: &mut Vec<EmitCommand>,
+    ) {
+        for (bit, sig) in settings.buttons.iter().enumerate() {
+            if mask & (1 << bit) == 0 {
+                continue;
+            }
+
+            if sig.index >= 5000 {
+                let key = match sig.index {
+                    5000 => Key::BTN_STYLUS,
+                    5001 => Key::BTN_STYLUS2,
+                    _ => continue,
+                };
+                chords.press(bit, std::slice::from_ref(&key), sig.index, out);
+            } else {
+                match settings.action_tablet_buttons.get(sig.index) {
+                    Some(ButtonAction::Keys(keys)) => chords.press(bit, keys, sig.index, out),
+                    Some(ButtonAction::Pad(key)) => {
+                        chords.press(bit, std::slice::from_ref(key), sig.index, out)
+                    }
+                    Some(ButtonAction::Scroll { modifier, horizontal, amount }) => {
+                        if let Some(key) = modifier {
+                            chords.press(bit, std::slice::from_ref(key), sig.index, out);
+                        }
+                        out.push(EmitCommand::Scroll {
+                            horizontal: *horizontal,
+                            amount: *amount,
+                        });
+                    }
+                    Some(ButtonAction::Eraser) => eraser.held |= 1 << bit,
+                    Some(ButtonAction::EraserToggle) => eraser.toggled = !eraser.toggled,
+                    None => {}
+                }
+            }
+        }
+    }
+
+    /// Libera as combinações de todos os botões presentes em `mask`.
+    fn release_buttons(
+        chords: &mut ChordState,
+        eraser: &mut EraserState,
+        settings: &TabletM100Settings,
+        mask: u32,
+        out: &mut Vec<EmitCommand>,
+    ) {
+        eraser.held &= !mask;
+
+        for bit in 0..MAX_BUTTONS {
+            if mask & (1 << bit) != 0 {
+                let index = settings.buttons.get(bit).map_or(bit, |sig| sig.index);
+                chords.release(bit, index, out);
+            }
         }
     }
 }
 
 impl Translator for TabletM100Translator {
-    /// Atualiza o tradutor a partir de uma nova configuração.
-    fn update_from_config(&mut self, cfg: &Config) {
-        self.pen_max_x = cfg.pen.max_x;
-        self.pen_max_y = cfg.pen.max_y;
-        self.pen_max_pressure = cfg.pen.max_pressure;
-        self.pen_resolution_x = cfg.pen.resolution_x;
-        self.pen_resolution_y = cfg.pen.resolution_y;
-
-        self.action_pen = Key::from_str(&cfg.actions.pen).unwrap();
-        self.action_stylus = Key::from_str(&cfg.actions.stylus).unwrap();
-        self.action_pen_touch = Key::from_str(&cfg.actions.pen_touch).unwrap();
-
-        self.action_tablet_buttons = cfg
+    type Settings = TabletM100Settings;
+
+    /// Interpreta a configuração, convertendo os nomes de teclas em [`Key`].
+    fn settings_from_config(cfg: &Config) -> Result<TabletM100Settings> {
+        // Converte botões configurados como combinações (ex: "Ctrl+Z") ou borracha
+        let action_tablet_buttons = cfg
             .actions
             .tablet_buttons
             .iter()
-            .map(|combo| {
-                combo
-                    .split('+')
-                    .filter_map(|k| Key::from_str(k).ok())
-                    .collect::<Vec<Key>>()
+            .enumerate()
+            .map(|(i, combo)| {
+                ButtonAction::parse(combo, i).with_context(|| format!("Botão {} do tablet", i + 1))
             })
-            .collect();
+            .collect::<Result<Vec<ButtonAction>>>()?;
+
+        let (pen_resolution_x, pen_resolution_y) = cfg.pen.resolution();
 
-        self.swap_axis = cfg.settings.swap_axis;
-        self.swap_direction_x = cfg.settings.swap_direction_x;
-        self.swap_direction_y = cfg.settings.swap_direction_y;
+        Ok(TabletM100Settings {
+            pen_max_x: cfg.pen.max_x,
+            pen_max_y: cfg.pen.max_y,
+            pen_max_pressure: cfg.pen.max_pressure,
+            pen_resolution_x,
+            pen_resolution_y,
+            action_pen: parse_key(&cfg.actions.pen).context("actions.pen")?,
+            action_stylus: parse_key(&cfg.actions.stylus).context("actions.stylus")?,
+            action_pen_touch: parse_key(&cfg.actions.pen_touch).context("actions.pen_touch")?,
+            action_tablet_buttons,
+            buttons: button_signatures(cfg.button_map.as_ref())?,
+            swap_axis: cfg.settings.swap_axis,
+            swap_direction_x: cfg.settings.swap_direction_x,
+            swap_direction_y: cfg.settings.swap_direction_y,
+            touch_threshold: cfg.settings.touch_threshold,
+            touch_hysteresis: cfg.settings.touch_hysteresis,
+        })
     }
 
-    /// Converte um buffer de bytes do dispositivo USB em uma lista de comandos interpretados.
+    /// Botões da caneta, ação de toque e as teclas de todas as combinações dos
+    /// botões do tablet, sem repetições. Inclui `BTN_TOOL_RUBBER` se algum botão
+    /// tiver uma ação de borracha e os botões de pad (`BTN_0`..) dos botões `PAD`.
+    fn emitted_keys(settings: &TabletM100Settings) -> Vec<Key> {
+        let mut keys = vec![Key::BTN_STYLUS, Key::BTN_STYLUS2];
+        keys.extend(settings.action_pen_touch.filter(|key| *key != Key::BTN_TOUCH));
+        for action in &settings.action_tablet_buttons {
+            match action {
+                ButtonAction::Keys(combo) => keys.extend(combo.iter().copied()),
+                ButtonAction::Pad(key) => keys.push(*key),
+                ButtonAction::Scroll { modifier, .. } => keys.extend(*modifier),
+                ButtonAction::Eraser | ButtonAction::EraserToggle => {
+                    keys.push(Key::BTN_TOOL_RUBBER)
+                }
+            }
+        }
+
+        keys.sort_by_key(|key| key.code());
+        keys.dedup();
+        keys
+    }
+
+    /// Converte um buffer de bytes do dispositivo USB em comandos interpretados.
     ///
     /// - Pacotes com `buf[1] == 192 ou 193` representam movimento da caneta
-    /// - Pacotes com `buf[0] == 2` representam botões físicos
-    fn conv(&self, buf: &Vec<u8>) -> Vec<EmitCommand> {
-        let mut out = Vec::new();
+    /// - Pacotes com `buf[1] == 0x80` indicam que a caneta saiu do alcance
+    /// - Pacotes cujo `buf[0]` é o ID de relatório de alguma assinatura representam botões físicos
+    fn conv(&mut self, buf: &[u8], out: &mut Vec<EmitCommand>) -> bool {
+        // Carrega o snapshot atual sem bloquear atualizações concorrentes
+        let settings = self.settings.load();
 
         // --- Movimento da caneta ---
         if buf.len() >= 8 && (buf[1] == 192 || buf[1] == 193) {
@@ -145,102 +462,401 @@ impl Translator for TabletM100Translator {
             let pressure = raw_pressure;
 
             // Aplica transformações configuradas
-            if self.swap_axis {
+            if settings.swap_axis {
                 std::mem::swap(&mut x, &mut y);
             }
-            if self.swap_direction_x {
-                x = self.pen_max_x as i32 - x;
+            if settings.swap_direction_x {
+                x = settings.pen_max_x as i32 - x;
             }
-            if self.swap_direction_y {
-                y = self.pen_max_y as i32 - y;
+            if settings.swap_direction_y {
+                y = settings.pen_max_y as i32 - y;
+            }
+
+            let touch = self.touch_state(&settings, buf[1] != 192, pressure);
+
+            if !self.in_range {
+                self.in_range = true;
+                out.push(EmitCommand::Proximity { in_range: true });
             }
 
             out.push(EmitCommand::Pen {
                 x,
                 y,
                 pressure,
-                touch: buf[1] != 192,
+                touch,
             });
+            self.set_touching(&settings, touch, out);
+
+            true
         }
 
-        // --- Botões ---
-        else if buf.len() >= 8 && buf[0] == 2 {
-            let mut pressed_keys = self.pressed_keys.lock().unwrap();
-            let mut current_keys = HashSet::new();
-
-            // Mapeamento estático do dispositivo
-            let button_mapping: Vec<(u8, u8, usize)> = vec![
-                (1, 28, 5000), // BTN_STYLUS
-                (1, 29, 5001), // BTN_STYLUS2
-                (1, 86, 0),    // Botão 1
-                (1, 87, 1),    // Botão 2
-                (0, 47, 2),    // Botão 3
-                (0, 48, 3),    // Botão 4
-                (0, 43, 4),    // Botão 5
-                (0, 44, 5),    // Botão 6
-                (1, 0, 6),     // Botão 7
-                (4, 0, 7),     // Botão 8
-            ];
-
-            // Identifica quais botões estão pressionados neste pacote
-            for (b1, b3, idx) in button_mapping.iter() {
-                if buf[1] == *b1 && buf[3] == *b3 {
-                    current_keys.insert(*idx);
-                }
+        // --- Caneta fora do alcance (não confundir com pacotes de botões) ---
+        else if buf.len() >= 8
+            && buf[1] == PEN_OUT_OF_RANGE
+            && !settings.buttons.iter().any(|sig| sig.report_id == buf[0])
+        {
+            if self.in_range {
+                self.set_touching(&settings, false, out);
+                self.in_range = false;
+                out.push(EmitCommand::Proximity { in_range: false });
             }
+            true
+        }
 
-            // Detecta botões pressionados
-            for idx in current_keys.difference(&pressed_keys) {
-                if *idx >= 5000 {
-                    let key = match *idx {
-                        5000 => Key::BTN_STYLUS,
-                        5001 => Key::BTN_STYLUS2,
-                        _ => continue,
-                    };
-                    out.push(EmitCommand::Btn {
-                        key: key.code() as i32,
-                        pressed: true,
-                        index: *idx,
-                    });
-                } else if let Some(keys) = self.action_tablet_buttons.get(*idx) {
-                    for k in keys {
-                        out.push(EmitCommand::Btn {
-                            key: k.code() as i32,
-                            pressed: true,
-                            index: *idx,
-                        });
-                    }
+        // --- Botões ---
+        else if buf.len() >= 8 {
+            // Identifica os botões reportados neste pacote e quais estão pressionados
+            let mut report_mask: u32 = 0;
+            let mut current: u32 = 0;
+            for (bit, sig) in settings.buttons.iter().enumerate() {
+                if sig.report_id != buf[0] {
+                    continue;
+                }
+                report_mask |= 1 << bit;
+                if sig.matches(buf) {
+                    current |= 1 << bit;
                 }
             }
 
-            // Detecta botões liberados
-            for idx in pressed_keys.difference(&current_keys) {
-                if *idx >= 5000 {
-                    let key = match *idx {
-                        5000 => Key::BTN_STYLUS,
-                        5001 => Key::BTN_STYLUS2,
-                        _ => continue,
-                    };
-                    out.push(EmitCommand::Btn {
-                        key: key.code() as i32,
-                        pressed: false,
-                        index: *idx,
-                    });
-                } else if let Some(keys) = self.action_tablet_buttons.get(*idx) {
-                    for k in keys {
-                        out.push(EmitCommand::Btn {
-                            key: k.code() as i32,
-                            pressed: false,
-                            index: *idx,
-                        });
-                    }
-                }
+            if report_mask == 0 {
+                return false;
             }
 
+            let previous = self.pressed_buttons & report_mask;
+
+            // Detecta botões pressionados e liberados. Os pressionamentos vêm
+            // primeiro para que modificadores compartilhados não sejam soltos
+            // e pressionados novamente na troca entre botões.
+            let (pressed, released) = (current & !previous, previous & !current);
+            Self::press_buttons(&mut self.chords, &mut self.eraser, &settings, pressed, out);
+            Self::release_buttons(&mut self.chords, &mut self.eraser, &settings, released, out);
+            self.eraser.update(out);
+
             // Atualiza estado
-            *pressed_keys = current_keys;
+            self.pressed_buttons = (self.pressed_buttons & !report_mask) | current;
+
+            true
+        } else {
+            false
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translator::translator::publish_config;
+    use arc_swap::ArcSwap;
+    use std::sync::Arc;
+
+    /// Pacote de botão do mapeamento embutido (`buf[1]`, `buf[3]`).
+    fn button(b1: u8, b3: u8) -> [u8; 8] {
+        [BUTTON_REPORT_ID, b1, 0, b3, 0, 0, 0, 0]
+    }
 
+    /// Pacote sem nenhum botão do tablet pressionado.
+    const NO_BUTTONS: [u8; 8] = [BUTTON_REPORT_ID, 0, 0, 0, 0, 0, 0, 0];
+
+    /// Pacote de caneta no alcance, sem contato.
+    const PEN_HOVER: [u8; 8] = [1, 192, 10, 0, 20, 0, 0, 0];
+
+    /// Pacote de caneta saindo do alcance.
+    const PEN_LEAVE: [u8; 8] = [1, PEN_OUT_OF_RANGE, 0, 0, 0, 0, 0, 0];
+
+    fn config(tablet_buttons: &[&str]) -> Config {
+        let yaml = format!(
+            "xinput_name: teste
+vendor_id: 0x08f2
+product_id: 0x6811
+endpoint: 0x85
+pen: {{ max_x: 4096, max_y: 4096, max_pressure: 2047, resolution_x: 20, resolution_y: 30 }}
+actions:
+  pen: BTN_TOOL_PEN
+  stylus: BTN_STYLUS
+  pen_touch: BTN_TOUCH
+  tablet_buttons: [{}]
+settings:
+  swap_axis: false
+  swap_direction_x: false
+  swap_direction_y: false
+  touch_threshold: 0
+  touch_hysteresis: 0
+",
+            tablet_buttons.join(", ")
+        );
+        serde_yaml::from_str(&yaml).expect("configuração de teste")
+    }
+
+    /// Cria o tradutor publicando `cfg` em um snapshot novo.
+    fn translator(cfg: &Config) -> TabletM100Translator {
+        let settings = TabletM100Translator::settings_from_config(cfg).expect("configuração válida");
+        let snapshot: ConfigSnapshot<TabletM100Settings> = Arc::new(ArcSwap::from_pointee(settings));
+        publish_config::<TabletM100Translator>(&snapshot, cfg).expect("configuração válida");
+        TabletM100Translator::new(snapshot)
+    }
+
+    /// Converte um pacote reconhecido e retorna os comandos gerados.
+    fn conv(translator: &mut TabletM100Translator, buf: &[u8]) -> Vec<EmitCommand> {
+        let mut out = Vec::new();
+        assert!(translator.conv(buf, &mut out), "pacote não reconhecido: {:?}", buf);
         out
     }
+
+    /// Substitui as assinaturas dos botões no snapshot do tradutor, permitindo
+    /// botões pressionados simultaneamente em um mesmo pacote.
+    fn with_buttons(translator: &TabletM100Translator, buttons: Vec<ButtonSignature>) {
+        let mut settings = TabletM100Settings::clone(&translator.settings.load());
+        settings.buttons = buttons;
+        translator.settings.store(Arc::new(settings));
+    }
+
+    /// Dois botões no relatório `BUTTON_REPORT_ID`: o primeiro em `buf[3] == 86`
+    /// e o segundo em `buf[4] == 1`, independentes entre si.
+    fn two_buttons() -> Vec<ButtonSignature> {
+        vec![
+            ButtonSignature { report_id: BUTTON_REPORT_ID, bytes: vec![(3, 86)], index: 0 },
+            ButtonSignature { report_id: BUTTON_REPORT_ID, bytes: vec![(4, 1)], index: 1 },
+        ]
+    }
+
+    /// Pacote com os botões de [`two_buttons`] pressionados conforme `first` e `second`.
+    fn buttons(first: bool, second: bool) -> [u8; 8] {
+        [BUTTON_REPORT_ID, 0, 0, if first { 86 } else { 0 }, second as u8, 0, 0, 0]
+    }
+
+    fn btn(key: Key, pressed: bool, index: usize) -> EmitCommand {
+        EmitCommand::Btn { key: key.code() as i32, pressed, index }
+    }
+
+    fn tool(eraser: bool) -> Vec<EmitCommand> {
+        vec![EmitCommand::Tool { eraser }]
+    }
+
+    #[test]
+    fn eraser_is_active_while_held() {
+        let mut t = translator(&config(&["ERASER"]));
+
+        assert_eq!(conv(&mut t, &button(1, 86)), tool(true));
+        assert_eq!(conv(&mut t, &button(1, 86)), vec![]);
+        assert_eq!(conv(&mut t, &NO_BUTTONS), tool(false));
+        assert_eq!(conv(&mut t, &NO_BUTTONS), vec![]);
+    }
+
+    #[test]
+    fn eraser_toggle_flips_on_each_press() {
+        let mut t = translator(&config(&["ERASER_TOGGLE"]));
+
+        assert_eq!(conv(&mut t, &button(1, 86)), tool(true));
+        assert_eq!(conv(&mut t, &NO_BUTTONS), vec![]);
+        assert_eq!(conv(&mut t, &button(1, 86)), tool(false));
+        assert_eq!(conv(&mut t, &NO_BUTTONS), vec![]);
+    }
+
+    #[test]
+    fn holding_eraser_inverts_toggled_tool() {
+        let mut t = translator(&config(&["ERASER", "ERASER_TOGGLE"]));
+
+        assert_eq!(conv(&mut t, &button(1, 87)), tool(true));
+        assert_eq!(conv(&mut t, &button(1, 86)), tool(false));
+        assert_eq!(conv(&mut t, &NO_BUTTONS), tool(true));
+    }
+
+    #[test]
+    fn eraser_toggle_out_of_range_applies_on_next_entry() {
+        let mut t = translator(&config(&["ERASER_TOGGLE"]));
+
+        conv(&mut t, &PEN_HOVER);
+        assert_eq!(conv(&mut t, &PEN_LEAVE), vec![EmitCommand::Proximity { in_range: false }]);
+
+        assert_eq!(conv(&mut t, &button(1, 86)), tool(true));
+        assert_eq!(conv(&mut t, &NO_BUTTONS), vec![]);
+
+        assert_eq!(
+            conv(&mut t, &PEN_HOVER),
+            vec![
+                EmitCommand::Proximity { in_range: true },
+                EmitCommand::Pen { x: 20, y: 10, pressure: 0, touch: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn chord_presses_modifiers_first_and_releases_in_reverse() {
+        let mut t = translator(&config(&["KEY_Z+KEY_LEFTSHIFT+KEY_LEFTCTRL"]));
+
+        assert_eq!(
+            conv(&mut t, &button(1, 86)),
+            vec![
+                btn(Key::KEY_LEFTSHIFT, true, 0),
+                btn(Key::KEY_LEFTCTRL, true, 0),
+                btn(Key::KEY_Z, true, 0),
+            ]
+        );
+        assert_eq!(conv(&mut t, &button(1, 86)), vec![]);
+        assert_eq!(
+            conv(&mut t, &NO_BUTTONS),
+            vec![
+                btn(Key::KEY_Z, false, 0),
+                btn(Key::KEY_LEFTCTRL, false, 0),
+                btn(Key::KEY_LEFTSHIFT, false, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn chords_sharing_a_modifier_keep_it_until_the_last_release() {
+        let mut t = translator(&config(&["KEY_LEFTCTRL+KEY_Z", "KEY_LEFTCTRL+KEY_LEFTSHIFT+KEY_Z"]));
+        with_buttons(&t, two_buttons());
+
+        assert_eq!(
+            conv(&mut t, &buttons(true, false)),
+            vec![btn(Key::KEY_LEFTCTRL, true, 0), btn(Key::KEY_Z, true, 0)]
+        );
+        assert_eq!(conv(&mut t, &buttons(true, true)), vec![btn(Key::KEY_LEFTSHIFT, true, 1)]);
+
+        // Soltar o primeiro não solta Ctrl nem Z, ainda usados pelo segundo
+        assert_eq!(conv(&mut t, &buttons(false, true)), vec![]);
+        assert_eq!(
+            conv(&mut t, &buttons(false, false)),
+            vec![
+                btn(Key::KEY_Z, false, 1),
+                btn(Key::KEY_LEFTSHIFT, false, 1),
+                btn(Key::KEY_LEFTCTRL, false, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn chords_released_in_press_order_release_shared_keys_last() {
+        let mut t = translator(&config(&["KEY_LEFTCTRL+KEY_Z", "KEY_LEFTCTRL+KEY_LEFTSHIFT+KEY_Z"]));
+        with_buttons(&t, two_buttons());
+
+        conv(&mut t, &buttons(true, false));
+        conv(&mut t, &buttons(true, true));
+
+        assert_eq!(conv(&mut t, &buttons(true, false)), vec![btn(Key::KEY_LEFTSHIFT, false, 1)]);
+        assert_eq!(
+            conv(&mut t, &buttons(false, false)),
+            vec![btn(Key::KEY_Z, false, 0), btn(Key::KEY_LEFTCTRL, false, 0)]
+        );
+    }
+
+    #[test]
+    fn switching_chords_in_one_packet_keeps_shared_keys_pressed() {
+        let mut t = translator(&config(&["KEY_LEFTCTRL+KEY_Z", "KEY_LEFTCTRL+KEY_LEFTSHIFT+KEY_Z"]));
+        with_buttons(&t, two_buttons());
+
+        conv(&mut t, &buttons(true, false));
+
+        // O pressionamento vem antes da liberação: Ctrl e Z não piscam
+        assert_eq!(conv(&mut t, &buttons(false, true)), vec![btn(Key::KEY_LEFTSHIFT, true, 1)]);
+        assert_eq!(
+            conv(&mut t, &buttons(false, false)),
+            vec![
+                btn(Key::KEY_Z, false, 1),
+                btn(Key::KEY_LEFTSHIFT, false, 1),
+                btn(Key::KEY_LEFTCTRL, false, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn chord_release_uses_keys_pressed_before_reconfiguration() {
+        let cfg = config(&["KEY_LEFTCTRL+KEY_Z"]);
+        let mut t = translator(&cfg);
+
+        conv(&mut t, &button(1, 86));
+        publish_config::<TabletM100Translator>(&t.settings, &config(&["KEY_C"])).unwrap();
+
+        assert_eq!(
+            conv(&mut t, &NO_BUTTONS),
+            vec![btn(Key::KEY_Z, false, 0), btn(Key::KEY_LEFTCTRL, false, 0)]
+        );
+    }
+
+    /// Tradutor com limiar de toque `threshold` e histerese `hysteresis`.
+    fn touch_translator(threshold: u32, hysteresis: u32, pen_touch: &str) -> TabletM100Translator {
+        let mut cfg = config(&[]);
+        cfg.settings.touch_threshold = threshold;
+        cfg.settings.touch_hysteresis = hysteresis;
+        cfg.actions.pen_touch = pen_touch.into();
+        translator(&cfg)
+    }
+
+    /// Pacote de caneta no alcance com a pressão e o indicador de contato do hardware.
+    fn pen(pressure: u16, hw_touch: bool) -> [u8; 8] {
+        let state = if hw_touch { 193 } else { 192 };
+        [1, state, 10, 0, 20, 0, (pressure % 255) as u8, (pressure / 255) as u8]
+    }
+
+    /// Estado de toque do evento de caneta gerado pelo pacote.
+    fn touch(translator: &mut TabletM100Translator, buf: &[u8]) -> bool {
+        conv(translator, buf)
+            .iter()
+            .find_map(|cmd| match cmd {
+                EmitCommand::Pen { touch, .. } => Some(*touch),
+                _ => None,
+            })
+            .expect("evento de caneta")
+    }
+
+    #[test]
+    fn touch_starts_at_threshold() {
+        let mut t = touch_translator(100, 30, "None");
+
+        assert!(!touch(&mut t, &pen(99, true)));
+        assert!(touch(&mut t, &pen(100, false)));
+    }
+
+    #[test]
+    fn touch_holds_down_to_hysteresis_and_releases_below() {
+        let mut t = touch_translator(100, 30, "None");
+
+        assert!(touch(&mut t, &pen(300, true)));
+        assert!(touch(&mut t, &pen(70, true)));
+        assert!(!touch(&mut t, &pen(69, true)));
+
+        // Depois de liberado, volta a exigir o limiar completo
+        assert!(!touch(&mut t, &pen(80, true)));
+        assert!(touch(&mut t, &pen(100, true)));
+    }
+
+    #[test]
+    fn touch_release_level_is_at_least_one() {
+        let mut t = touch_translator(10, 50, "None");
+
+        assert!(touch(&mut t, &pen(10, false)));
+        assert!(touch(&mut t, &pen(1, false)));
+        assert!(!touch(&mut t, &pen(0, true)));
+    }
+
+    #[test]
+    fn zero_threshold_uses_hardware_touch() {
+        let mut t = touch_translator(0, 30, "None");
+
+        assert!(!touch(&mut t, &pen(500, false)));
+        assert!(touch(&mut t, &pen(0, true)));
+        assert!(!touch(&mut t, &pen(0, false)));
+    }
+
+    #[test]
+    fn leaving_proximity_clears_touching() {
+        let mut t = touch_translator(100, 30, "KEY_X");
+
+        assert_eq!(
+            conv(&mut t, &pen(100, true)),
+            vec![
+                EmitCommand::Proximity { in_range: true },
+                EmitCommand::Pen { x: 20, y: 10, pressure: 100, touch: true },
+                btn(Key::KEY_X, true, PEN_TOUCH_INDEX),
+            ]
+        );
+        assert_eq!(
+            conv(&mut t, &PEN_LEAVE),
+            vec![btn(Key::KEY_X, false, PEN_TOUCH_INDEX), EmitCommand::Proximity { in_range: false }]
+        );
+
+        // Ao voltar, a pressão abaixo do limiar não retoma o toque anterior
+        assert!(!touch(&mut t, &pen(80, true)));
+    }
 }
@@ -1,7 +1,22 @@
+use anyhow::Result;
+use arc_swap::ArcSwap;
 use bincode::{Decode, Encode};
+use evdev::Key;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use table_z_config::Config;
 
+/// Snapshot imutável de configuração, trocado atomicamente.
+///
+/// A thread de leitura apenas carrega o snapshot atual (sem lock) a cada pacote,
+/// enquanto atualizações publicam um novo snapshot completo. Assim uma atualização
+/// nunca bloqueia o processamento de pacotes.
+pub type ConfigSnapshot<T> = Arc<ArcSwap<T>>;
+
+/// Primeiro índice de [`EmitCommand::Btn`] reservado às ações da caneta
+/// (botões laterais e toque); índices menores são botões físicos do tablet.
+pub const PEN_BUTTON_INDEX: usize = 5000;
+
 /// Representa um comando interpretado e pronto para ser emitido pelo sistema.
 ///
 /// Esses comandos são normalmente produzidos por um [`Translator`],
@@ -9,7 +24,7 @@ use table_z_config::Config;
 ///
 /// Cada variante representa uma ação lógica detectada no dispositivo —
 /// como movimento da caneta ou pressionamento de botão.
-#[derive(Debug, Clone, Encode, Decode, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize, Deserialize)]
 pub enum EmitCommand {
     /// Evento da caneta, representando posição e pressão atuais.
     Pen {
@@ -23,13 +38,42 @@ pub enum EmitCommand {
         touch: bool,
     },
 
+    /// A caneta entrou (`true`) ou saiu (`false`) do alcance da mesa.
+    ///
+    /// Emitido antes do primeiro [`EmitCommand::Pen`] ao entrar e após o
+    /// último ao sair, para que a caneta virtual sinalize a ferramenta ativa.
+    Proximity {
+        /// Caneta no alcance da mesa.
+        in_range: bool,
+    },
+
+    /// Troca a ferramenta da caneta entre ponta (`false`) e borracha (`true`).
+    ///
+    /// Pode chegar com a caneta dentro ou fora do alcance; a caneta virtual
+    /// sinaliza a nova ferramenta na próxima entrada em proximidade ou, se já
+    /// estiver no alcance, retirando a ferramenta anterior antes.
+    Tool {
+        /// Borracha ativa.
+        eraser: bool,
+    },
+
+    /// Rolagem do mouse virtual.
+    Scroll {
+        /// Rolagem horizontal (`true`) ou vertical (`false`).
+        horizontal: bool,
+        /// Unidades de alta resolução (120 por passo da roda); positivo rola
+        /// para cima ou para a direita.
+        amount: i32,
+    },
+
     /// Evento de botão físico no tablet.
     Btn {
         /// Código da tecla (keycode, normalmente compatível com X11 ou HID).
         key: i32,
         /// Indica se o botão foi pressionado (`true`) ou solto (`false`).
         pressed: bool,
-        /// Índice do botão físico na mesa digitalizadora.
+        /// Índice do botão físico na mesa digitalizadora (a partir de
+        /// [`PEN_BUTTON_INDEX`] para as ações da caneta).
         index: usize,
     },
 }
@@ -40,31 +84,60 @@ pub enum EmitCommand {
 /// ou dispositivos, mantendo uma interface genérica e extensível.
 ///
 /// O implementador dessa trait normalmente analisa bytes do firmware do dispositivo
-/// e retorna uma lista de [`EmitCommand`] representando os eventos reconhecidos.
-pub trait Translator: Send + Sync {
-    /// Converte um pacote binário (raw USB data) em uma lista de [`EmitCommand`].
+/// e escreve em um vetor fornecido pelo chamador os [`EmitCommand`] representando os eventos reconhecidos.
+pub trait Translator: Send {
+    /// Configuração já interpretada e validada, consumida pelo tradutor a cada pacote.
+    type Settings: Send + Sync + 'static;
+
+    /// Interpreta e valida uma [`Config`], produzindo um novo snapshot de configuração.
+    ///
+    /// # Erros
+    /// Retorna erro se a configuração for inválida (ex: nome de tecla desconhecido).
+    fn settings_from_config(cfg: &Config) -> Result<Self::Settings>;
+
+    /// Teclas que o tradutor pode emitir com a configuração `settings`.
+    ///
+    /// Usadas para anunciar no dispositivo de botões apenas as teclas realmente
+    /// utilizadas, em vez de um teclado completo.
+    fn emitted_keys(settings: &Self::Settings) -> Vec<Key>;
+
+    /// Converte um pacote binário (raw USB data) em [`EmitCommand`]s, acrescentados em `out`.
+    ///
+    /// O vetor `out` pertence ao chamador e deve ser reutilizado entre pacotes
+    /// (limpando-o antes de cada chamada), evitando alocações no caminho de leitura.
     ///
     /// # Parâmetros
     /// - `buf`: Buffer recebido diretamente da USB (ex: leitura via `rusb::read_interrupt`).
+    /// - `out`: Destino dos comandos interpretados, prontos para serem processados ou emitidos.
     ///
     /// # Retorno
-    /// Uma lista de comandos interpretados, prontos para serem processados ou emitidos.
+    /// `true` se o pacote foi reconhecido (mesmo sem gerar comandos), `false` se
+    /// o formato for desconhecido; usado nas estatísticas de falhas de decodificação.
     ///
     /// # Exemplo
-    /// ```
-    /// let commands = translator.conv(&packet);
-    /// for cmd in commands {
+    /// ```ignore
+    /// let mut commands = Vec::with_capacity(8);
+    /// commands.clear();
+    /// translator.conv(&packet, &mut commands);
+    /// for cmd in &commands {
     ///     println!("Evento: {:?}", cmd);
     /// }
     /// ```
-    fn conv(&self, buf: &Vec<u8>) -> Vec<EmitCommand>;
+    fn conv(&mut self, buf: &[u8], out: &mut Vec<EmitCommand>) -> bool;
+}
 
-    /// Atualiza a lógica de tradução a partir de uma nova configuração.
-    ///
-    /// # Parâmetros
-    /// - `cfg`: Estrutura [`Config`] contendo dados como mapeamento de botões e resolução da mesa.
-    ///
-    /// Essa função permite reconfigurar o tradutor sem precisar reinicializá-lo,
-    /// por exemplo, quando o usuário altera preferências no software.
-    fn update_from_config(&mut self, cfg: &Config);
+/// Publica uma nova configuração no snapshot compartilhado por tradutores do tipo `T`.
+///
+/// A configuração é validada antes da troca: se for inválida, o erro é retornado
+/// e o snapshot anterior permanece ativo.
+///
+/// Essa função permite reconfigurar os tradutores sem reinicializá-los,
+/// por exemplo, quando o usuário altera preferências no software.
+pub fn publish_config<T: Translator>(
+    snapshot: &ConfigSnapshot<T::Settings>,
+    cfg: &Config,
+) -> Result<()> {
+    let settings = T::settings_from_config(cfg)?;
+    snapshot.store(Arc::new(settings));
+    Ok(())
 }
@@ -1,46 +1,109 @@
 use evdev::{
-    AbsInfo, AbsoluteAxisType, AttributeSet, EventType, InputEvent, Key, UinputAbsSetup,
-    uinput::VirtualDeviceBuilder,
+    AbsInfo, AbsoluteAxisType, AttributeSet, EventType, InputEvent, Key, PropType,
+    RelativeAxisType, UinputAbsSetup, uinput::VirtualDeviceBuilder,
 };
 use std::sync::{Arc, Mutex};
 use anyhow::Result;
 
+/// Botões da caneta emitidos pelo próprio dispositivo de caneta.
+pub const PEN_BUTTONS: [Key; 2] = [Key::BTN_STYLUS, Key::BTN_STYLUS2];
+
+/// Botões emitidos pelo dispositivo de mouse.
+pub const MOUSE_BUTTONS: [Key; 3] = [Key::BTN_LEFT, Key::BTN_RIGHT, Key::BTN_MIDDLE];
+
+/// Unidades de rolagem de alta resolução por passo da roda (`REL_WHEEL_HI_RES`).
+pub const WHEEL_DETENT: i32 = 120;
+
+/// Último estado emitido pela caneta, para enviar apenas o que mudou.
+#[derive(Default)]
+struct PenState {
+    /// Ferramenta sinalizada (`BTN_TOOL_PEN` ou `BTN_TOOL_RUBBER`)
+    in_range: bool,
+    /// Borracha selecionada como ferramenta
+    eraser: bool,
+    x: i32,
+    y: i32,
+    pressure: i32,
+    touch: bool,
+    /// Botões da caneta pressionados
+    buttons: Vec<Key>,
+    /// Eventos do frame em construção, reutilizado entre chamadas
+    frame: Vec<InputEvent>,
+}
+
+impl PenState {
+    /// Tecla da ferramenta selecionada.
+    fn tool(&self) -> Key {
+        if self.eraser { Key::BTN_TOOL_RUBBER } else { Key::BTN_TOOL_PEN }
+    }
+
+    /// Acrescenta ao frame a saída de proximidade: toque e botões ativos são
+    /// liberados antes de a ferramenta ser desligada.
+    fn push_leave(&mut self) {
+        if self.touch {
+            self.frame.push(key_event(Key::BTN_TOUCH, false));
+        }
+        for key in self.buttons.drain(..) {
+            self.frame.push(key_event(key, false));
+        }
+        if self.pressure != 0 {
+            self.frame.push(abs_event(AbsoluteAxisType::ABS_PRESSURE, 0));
+        }
+        self.frame.push(key_event(self.tool(), false));
+
+        self.in_range = false;
+        self.touch = false;
+        self.pressure = 0;
+    }
+}
+
 /// Representa uma caneta virtual (pen) criada via `uinput`.
 ///
-/// Este dispositivo é responsável por emitir eventos de posição (X/Y),
-/// pressão e toque (`BTN_TOUCH`), simulando uma mesa digitalizadora.
+/// O dispositivo é classificado como ferramenta de mesa digitalizadora pelo
+/// libinput: anuncia `INPUT_PROP_POINTER` (ou `INPUT_PROP_DIRECT`, em mesas com
+/// tela), sinaliza a proximidade com `BTN_TOOL_PEN` (ou `BTN_TOOL_RUBBER`, com a
+/// borracha selecionada) e emite os botões da caneta (`BTN_STYLUS`,
+/// `BTN_STYLUS2`) junto com os eixos.
 ///
 /// ### Características
-/// - Suporte a eixos absolutos (`ABS_X`, `ABS_Y`, `ABS_PRESSURE`)
-/// - Pode ser usado em conjunto com um tradutor de pacotes USB
+/// - Suporte a eixos absolutos (`ABS_X`, `ABS_Y`, `ABS_PRESSURE`), com
+///   resolução em unidades/mm
+/// - Cada frame contém apenas os eixos e botões alterados
 /// - É thread-safe via `Arc<Mutex<_>>`, permitindo uso concorrente
 #[derive(Clone)]
 pub struct VPen {
     /// Dispositivo virtual do `uinput`, protegido por `Mutex` para acesso seguro.
     pub device: Arc<Mutex<evdev::uinput::VirtualDevice>>,
+    /// Último estado emitido
+    state: Arc<Mutex<PenState>>,
+    /// Anuncia a borracha (`BTN_TOOL_RUBBER`)
+    rubber: bool,
 }
 
 impl VPen {
-    /// Cria um novo dispositivo de caneta virtual configurado com os eixos e teclas fornecidos.
+    /// Cria um novo dispositivo de caneta virtual configurado com os eixos fornecidos.
     ///
     /// # Parâmetros
     /// - `x_max`: Valor máximo do eixo X
     /// - `y_max`: Valor máximo do eixo Y
     /// - `pressure_max`: Valor máximo da pressão detectável
-    /// - `res_x`: Resolução (DPI) no eixo X
-    /// - `res_y`: Resolução (DPI) no eixo Y
-    /// - `keys`: Lista de teclas (geralmente contém `BTN_TOUCH`, `BTN_TOOL_PEN`, etc.)
+    /// - `res_x`: Resolução do eixo X, em unidades/mm
+    /// - `res_y`: Resolução do eixo Y, em unidades/mm
+    /// - `direct`: `true` para mesas com tela (`INPUT_PROP_DIRECT`)
+    /// - `rubber`: `true` para anunciar a borracha (`BTN_TOOL_RUBBER`)
     /// - `name`: Nome do dispositivo a ser criado (aparece em `/dev/input/by-id`)
     ///
     /// # Retorno
     /// Retorna `Result<Self>` com a instância de `VPen` pronta para uso.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         x_max: i32,
         y_max: i32,
         pressure_max: i32,
         res_x: i32,
         res_y: i32,
-        keys: &[Key],
+        direct: bool,
+        rubber: bool,
         name: &str,
     ) -> Result<Self> {
         // Configuração dos eixos absolutos (posição e pressão)
@@ -57,10 +120,24 @@ impl VPen {
             AbsInfo::new(0, 0, pressure_max, 0, 0, 0),
         );
 
+        let mut keys = AttributeSet::<Key>::new();
+        keys.insert(Key::BTN_TOUCH);
+        keys.insert(Key::BTN_TOOL_PEN);
+        if rubber {
+            keys.insert(Key::BTN_TOOL_RUBBER);
+        }
+        for key in PEN_BUTTONS {
+            keys.insert(key);
+        }
+
+        let mut properties = AttributeSet::<PropType>::new();
+        properties.insert(if direct { PropType::DIRECT } else { PropType::POINTER });
+
         // Criação do dispositivo virtual
         let dev = VirtualDeviceBuilder::new()?
             .name(name)
-            .with_keys(&AttributeSet::from_iter(keys.iter().cloned()))?
+            .with_properties(&properties)?
+            .with_keys(&keys)?
             .with_absolute_axis(&abs_x)?
             .with_absolute_axis(&abs_y)?
             .with_absolute_axis(&abs_pressure)?
@@ -68,10 +145,23 @@ impl VPen {
 
         Ok(Self {
             device: Arc::new(Mutex::new(dev)),
+            state: Arc::new(Mutex::new(PenState {
+                frame: Vec::with_capacity(8),
+                ..PenState::default()
+            })),
+            rubber,
         })
     }
 
-    /// Emite um conjunto de eventos simulando movimento e toque da caneta.
+    /// Retorna `true` se `key` for um botão emitido pela caneta.
+    pub fn handles(key: Key) -> bool {
+        PEN_BUTTONS.contains(&key)
+    }
+
+    /// Emite posição, pressão e toque da caneta.
+    ///
+    /// Sinaliza a entrada em proximidade da ferramenta selecionada se necessário; fora
+    /// isso, apenas os valores alterados desde o último frame são enviados.
     ///
     /// # Parâmetros
     /// - `x`: Posição X absoluta
@@ -80,32 +170,133 @@ impl VPen {
     /// - `touch`: `true` se houver contato (toque ativo)
     ///
     /// # Exemplo
-    /// ```
+    /// ```ignore
     /// pen.emit(1200, 800, 300, true)?;
     /// ```
     pub fn emit(&self, x: i32, y: i32, pressure: i32, touch: bool) -> Result<(), std::io::Error> {
-        let events = [
-            InputEvent::new(EventType::ABSOLUTE, AbsoluteAxisType::ABS_X.0, x),
-            InputEvent::new(EventType::ABSOLUTE, AbsoluteAxisType::ABS_Y.0, y),
-            InputEvent::new(
-                EventType::ABSOLUTE,
-                AbsoluteAxisType::ABS_PRESSURE.0,
-                pressure,
-            ),
-            InputEvent::new(
-                EventType::KEY,
-                Key::BTN_TOUCH.code(),
-                if touch { 1 } else { 0 },
-            ),
-        ];
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        state.frame.clear();
+
+        // Ao entrar em proximidade todos os eixos são enviados
+        let entering = !state.in_range;
+        if entering {
+            state.in_range = true;
+            state.frame.push(key_event(state.tool(), true));
+        }
+
+        if entering || x != state.x {
+            state.frame.push(abs_event(AbsoluteAxisType::ABS_X, x));
+        }
+        if entering || y != state.y {
+            state.frame.push(abs_event(AbsoluteAxisType::ABS_Y, y));
+        }
+        if entering || pressure != state.pressure {
+            state.frame.push(abs_event(AbsoluteAxisType::ABS_PRESSURE, pressure));
+        }
+        if touch != state.touch {
+            state.frame.push(key_event(Key::BTN_TOUCH, touch));
+        }
+        (state.x, state.y, state.pressure, state.touch) = (x, y, pressure, touch);
+
+        self.flush(&mut state.frame)
+    }
+
+    /// Pressiona ou solta um botão da caneta ([`PEN_BUTTONS`]).
+    pub fn button(&self, key: Key, pressed: bool) -> Result<(), std::io::Error> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        state.frame.clear();
+
+        let held = state.buttons.contains(&key);
+        if pressed && !held {
+            state.buttons.push(key);
+        } else if !pressed && held {
+            state.buttons.retain(|k| *k != key);
+        } else {
+            return Ok(());
+        }
+        state.frame.push(key_event(key, pressed));
+
+        self.flush(&mut state.frame)
+    }
+
+    /// Sinaliza que a caneta saiu do alcance da mesa.
+    ///
+    /// Toque e botões ainda ativos são liberados no mesmo frame, antes de
+    /// a ferramenta ser desligada.
+    pub fn leave(&self) -> Result<(), std::io::Error> {
+        let mut state = self.state.lock().unwrap();
+        if !state.in_range {
+            return Ok(());
+        }
+        state.frame.clear();
+        state.push_leave();
+
+        self.flush(&mut state.frame)
+    }
+
+    /// Seleciona a ponta (`false`) ou a borracha (`true`) como ferramenta.
+    ///
+    /// Fora do alcance, a nova ferramenta é sinalizada na próxima entrada em
+    /// proximidade. No alcance, a ferramenta anterior sai de proximidade em um
+    /// frame e a nova entra no seguinte, na mesma posição e com os botões da
+    /// caneta ainda pressionados, para que as aplicações troquem de pincel; um
+    /// traço em andamento é encerrado. Ignorado se a borracha não foi anunciada.
+    pub fn set_tool(&self, eraser: bool) -> Result<(), std::io::Error> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        if state.eraser == eraser || (eraser && !self.rubber) {
+            return Ok(());
+        }
+        if !state.in_range {
+            state.eraser = eraser;
+            return Ok(());
+        }
+
+        let buttons = state.buttons.clone();
+        state.frame.clear();
+        state.push_leave();
+        self.flush(&mut state.frame)?;
+
+        state.eraser = eraser;
+        state.in_range = true;
+        state.frame.push(key_event(state.tool(), true));
+        state.frame.push(abs_event(AbsoluteAxisType::ABS_X, state.x));
+        state.frame.push(abs_event(AbsoluteAxisType::ABS_Y, state.y));
+        state.frame.push(abs_event(AbsoluteAxisType::ABS_PRESSURE, 0));
+        for key in buttons {
+            state.frame.push(key_event(key, true));
+            state.buttons.push(key);
+        }
+
+        self.flush(&mut state.frame)
+    }
+
+    /// Envia o frame (seguido de `SYN_REPORT`), se houver eventos.
+    fn flush(&self, frame: &mut Vec<InputEvent>) -> Result<(), std::io::Error> {
+        if frame.is_empty() {
+            return Ok(());
+        }
 
         // Bloqueia o dispositivo antes de enviar eventos
         let mut dev = self.device.lock().unwrap();
-        dev.emit(&events)?;
+        dev.emit(frame)?;
+        frame.clear();
         Ok(())
     }
 }
 
+/// Evento `EV_KEY`.
+fn key_event(key: Key, pressed: bool) -> InputEvent {
+    InputEvent::new(EventType::KEY, key.code(), pressed as i32)
+}
+
+/// Evento `EV_ABS`.
+fn abs_event(axis: AbsoluteAxisType, value: i32) -> InputEvent {
+    InputEvent::new(EventType::ABSOLUTE, axis.0, value)
+}
+
 /// Representa um dispositivo virtual de botões (sem eixos),
 /// responsável por emitir eventos de teclas.
 ///
@@ -114,6 +305,8 @@ impl VPen {
 pub struct VBtn {
     /// Dispositivo virtual protegido por Mutex.
     pub device: Arc<Mutex<evdev::uinput::VirtualDevice>>,
+    /// Códigos das teclas atualmente pressionadas, liberadas por [`VBtn::release_all`].
+    held: Arc<Mutex<Vec<u16>>>,
 }
 
 impl VBtn {
@@ -130,9 +323,24 @@ impl VBtn {
 
         Ok(Self {
             device: Arc::new(Mutex::new(dev)),
+            held: Arc::new(Mutex::new(Vec::with_capacity(8))),
         })
     }
 
+    /// Registra as transições de `events` no conjunto de teclas pressionadas.
+    fn track(&self, events: &[InputEvent]) {
+        let mut held = self.held.lock().unwrap();
+        for event in events {
+            if event.event_type() != EventType::KEY {
+                continue;
+            }
+            held.retain(|code| *code != event.code());
+            if event.value() != 0 {
+                held.push(event.code());
+            }
+        }
+    }
+
     /// Emite um evento de tecla pressionada ou liberada.
     ///
     /// # Parâmetros
@@ -140,7 +348,7 @@ impl VBtn {
     /// - `value`: `true` para pressionar, `false` para soltar
     ///
     /// # Exemplo
-    /// ```
+    /// ```ignore
     /// btn.emit(Key::KEY_A, true)?;  // Pressiona 'A'
     /// btn.emit(Key::KEY_A, false)?; // Solta 'A'
     /// ```
@@ -149,6 +357,234 @@ impl VBtn {
         let event = InputEvent::new(EventType::KEY, key.code(), pressed);
         let mut dev = self.device.lock().unwrap();
         dev.emit(&[event])?;
+        self.track(&[event]);
+        Ok(())
+    }
+
+    /// Emite um conjunto de eventos de tecla em um único frame (`SYN_REPORT` ao final).
+    ///
+    /// Usado para agrupar as teclas de uma combinação (ex: `Ctrl+Z`), de modo
+    /// que a aplicação receba a transição completa de uma só vez.
+    ///
+    /// # Parâmetros
+    /// - `events`: Eventos `EV_KEY` já ordenados
+    pub fn emit_frame(&self, events: &[InputEvent]) -> Result<()> {
+        let mut dev = self.device.lock().unwrap();
+        dev.emit(events)?;
+        self.track(events);
         Ok(())
     }
+
+    /// Solta todas as teclas ainda pressionadas, em ordem inversa.
+    ///
+    /// Usado quando o dispositivo físico é desconectado com botões pressionados,
+    /// já que o dispositivo virtual continua existindo.
+    pub fn release_all(&self) -> Result<()> {
+        let held: Vec<u16> = std::mem::take(&mut *self.held.lock().unwrap());
+        if held.is_empty() {
+            return Ok(());
+        }
+
+        let events: Vec<InputEvent> = held
+            .iter()
+            .rev()
+            .map(|code| InputEvent::new(EventType::KEY, *code, 0))
+            .collect();
+        let mut dev = self.device.lock().unwrap();
+        dev.emit(&events)?;
+        Ok(())
+    }
+}
+
+/// Representa o dispositivo de pad (botões da mesa) criado via `uinput`.
+///
+/// Expõe os botões físicos configurados como `PAD` nos códigos nativos
+/// `BTN_0`..`BTN_7`, em um dispositivo separado cujo nome termina em `Pad`.
+/// Assim o libinput o classifica como pad de mesa digitalizadora e as
+/// aplicações e configurações do desktop podem associar ações aos botões.
+#[derive(Clone)]
+pub struct VPad {
+    /// Dispositivo virtual protegido por Mutex.
+    pub device: Arc<Mutex<evdev::uinput::VirtualDevice>>,
+    /// Botões atualmente pressionados, liberados por [`VPad::release_all`].
+    held: Arc<Mutex<Vec<Key>>>,
+}
+
+impl VPad {
+    /// Quantidade de botões de pad disponíveis (`BTN_0`..`BTN_7`).
+    pub const MAX_BUTTONS: usize = 8;
+
+    /// Cria o dispositivo de pad anunciando os botões `buttons`.
+    ///
+    /// Os eixos `ABS_X`/`ABS_Y` (sem uso) são exigidos pelo udev para
+    /// identificar o dispositivo como pad (`ID_INPUT_TABLET_PAD`).
+    ///
+    /// # Parâmetros
+    /// - `buttons`: Botões anunciados (`BTN_0`..`BTN_7`)
+    /// - `name`: Nome do dispositivo
+    pub fn new(buttons: &[Key], name: &str) -> Result<Self> {
+        let abs_x = UinputAbsSetup::new(AbsoluteAxisType::ABS_X, AbsInfo::new(0, 0, 1, 0, 0, 0));
+        let abs_y = UinputAbsSetup::new(AbsoluteAxisType::ABS_Y, AbsInfo::new(0, 0, 1, 0, 0, 0));
+
+        let dev = VirtualDeviceBuilder::new()?
+            .name(name)
+            .with_keys(&AttributeSet::from_iter(buttons.iter().cloned()))?
+            .with_absolute_axis(&abs_x)?
+            .with_absolute_axis(&abs_y)?
+            .build()?;
+
+        Ok(Self {
+            device: Arc::new(Mutex::new(dev)),
+            held: Arc::new(Mutex::new(Vec::with_capacity(4))),
+        })
+    }
+
+    /// Botão de pad correspondente ao botão físico `index` (`BTN_0 + index`).
+    pub fn button_key(index: usize) -> Option<Key> {
+        (index < Self::MAX_BUTTONS).then(|| Key::new(Key::BTN_0.code() + index as u16))
+    }
+
+    /// Retorna `true` se `key` for um botão de pad.
+    pub fn handles(key: Key) -> bool {
+        (Key::BTN_0.code()..Key::BTN_0.code() + Self::MAX_BUTTONS as u16).contains(&key.code())
+    }
+
+    /// Pressiona ou solta um botão de pad.
+    pub fn button(&self, key: Key, pressed: bool) -> Result<(), std::io::Error> {
+        let mut held = self.held.lock().unwrap();
+        held.retain(|k| *k != key);
+        if pressed {
+            held.push(key);
+        }
+
+        let mut dev = self.device.lock().unwrap();
+        dev.emit(&[key_event(key, pressed)])
+    }
+
+    /// Solta todos os botões ainda pressionados.
+    pub fn release_all(&self) -> Result<(), std::io::Error> {
+        let held: Vec<Key> = std::mem::take(&mut *self.held.lock().unwrap());
+        if held.is_empty() {
+            return Ok(());
+        }
+
+        let events: Vec<InputEvent> = held.iter().map(|key| key_event(*key, false)).collect();
+        let mut dev = self.device.lock().unwrap();
+        dev.emit(&events)
+    }
+}
+
+/// Estado do mouse virtual.
+#[derive(Default)]
+struct MouseState {
+    /// Botões atualmente pressionados, liberados por [`VMouse::release_all`]
+    held: Vec<Key>,
+    /// Rolagem acumulada que ainda não completou um passo (vertical, horizontal)
+    remainder: [i32; 2],
+}
+
+/// Representa um mouse virtual criado via `uinput`, usado para rolagem e
+/// cliques disparados pelos botões da mesa.
+///
+/// ### Características
+/// - Botões `BTN_LEFT`, `BTN_RIGHT` e `BTN_MIDDLE`
+/// - Rolagem vertical (`REL_WHEEL`) e horizontal (`REL_HWHEEL`), acompanhada
+///   dos eventos de alta resolução (`REL_WHEEL_HI_RES`, `REL_HWHEEL_HI_RES`)
+/// - Anuncia `REL_X`/`REL_Y` (sem uso) para ser classificado como mouse pelo udev
+#[derive(Clone)]
+pub struct VMouse {
+    /// Dispositivo virtual protegido por Mutex.
+    pub device: Arc<Mutex<evdev::uinput::VirtualDevice>>,
+    state: Arc<Mutex<MouseState>>,
+}
+
+impl VMouse {
+    /// Cria o mouse virtual.
+    ///
+    /// # Parâmetros
+    /// - `name`: Nome do dispositivo
+    pub fn new(name: &str) -> Result<Self> {
+        let axes = AttributeSet::from_iter([
+            RelativeAxisType::REL_X,
+            RelativeAxisType::REL_Y,
+            RelativeAxisType::REL_WHEEL,
+            RelativeAxisType::REL_HWHEEL,
+            RelativeAxisType::REL_WHEEL_HI_RES,
+            RelativeAxisType::REL_HWHEEL_HI_RES,
+        ]);
+
+        let dev = VirtualDeviceBuilder::new()?
+            .name(name)
+            .with_keys(&AttributeSet::from_iter(MOUSE_BUTTONS))?
+            .with_relative_axes(&axes)?
+            .build()?;
+
+        Ok(Self {
+            device: Arc::new(Mutex::new(dev)),
+            state: Arc::new(Mutex::new(MouseState::default())),
+        })
+    }
+
+    /// Retorna `true` se `key` for um botão emitido pelo mouse.
+    pub fn handles(key: Key) -> bool {
+        MOUSE_BUTTONS.contains(&key)
+    }
+
+    /// Pressiona ou solta um botão do mouse ([`MOUSE_BUTTONS`]).
+    pub fn button(&self, key: Key, pressed: bool) -> Result<(), std::io::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.held.retain(|k| *k != key);
+        if pressed {
+            state.held.push(key);
+        }
+
+        let mut dev = self.device.lock().unwrap();
+        dev.emit(&[key_event(key, pressed)])
+    }
+
+    /// Rola `amount` unidades de alta resolução ([`WHEEL_DETENT`] por passo).
+    ///
+    /// Valores positivos rolam para cima (vertical) ou para a direita
+    /// (horizontal). O evento de alta resolução é sempre enviado; o evento
+    /// clássico, a cada passo completo acumulado.
+    ///
+    /// # Exemplo
+    /// ```ignore
+    /// mouse.scroll(false, WHEEL_DETENT)?; // Um passo para cima
+    /// ```
+    pub fn scroll(&self, horizontal: bool, amount: i32) -> Result<(), std::io::Error> {
+        let (wheel, hi_res, slot) = if horizontal {
+            (RelativeAxisType::REL_HWHEEL, RelativeAxisType::REL_HWHEEL_HI_RES, 1)
+        } else {
+            (RelativeAxisType::REL_WHEEL, RelativeAxisType::REL_WHEEL_HI_RES, 0)
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let total = state.remainder[slot] + amount;
+        let detents = total / WHEEL_DETENT;
+        state.remainder[slot] = total % WHEEL_DETENT;
+
+        let mut events = Vec::with_capacity(2);
+        if detents != 0 {
+            events.push(InputEvent::new(EventType::RELATIVE, wheel.0, detents));
+        }
+        events.push(InputEvent::new(EventType::RELATIVE, hi_res.0, amount));
+
+        let mut dev = self.device.lock().unwrap();
+        dev.emit(&events)
+    }
+
+    /// Solta os botões ainda pressionados e descarta a rolagem acumulada.
+    pub fn release_all(&self) -> Result<(), std::io::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.remainder = [0; 2];
+        let held = std::mem::take(&mut state.held);
+        if held.is_empty() {
+            return Ok(());
+        }
+
+        let events: Vec<InputEvent> = held.iter().map(|key| key_event(*key, false)).collect();
+        let mut dev = self.device.lock().unwrap();
+        dev.emit(&events)
+    }
 }
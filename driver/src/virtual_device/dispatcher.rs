@@ -0,0 +1,155 @@
+use anyhow::Result;
+use evdev::{EventType, InputEvent, Key};
+use std::sync::Arc;
+use table_z_config::Config;
+
+use crate::translator::translator::{EmitCommand, PEN_BUTTON_INDEX};
+use crate::virtual_device::{
+    DeviceCapabilities, DeviceLease, VBtn, VMouse, VPad, VPen, VirtualDevices,
+};
+
+/// Encaminha os [`EmitCommand`] produzidos por um tradutor para os dispositivos virtuais.
+///
+/// - Eventos de caneta, proximidade, troca de ferramenta e botões da caneta
+///   (`BTN_STYLUS*`) vão para o [`VPen`];
+/// - Botões de pad (`BTN_0`..`BTN_7`) vão para o [`VPad`], se existir;
+/// - Rolagem e botões de mouse (`BTN_LEFT`, `BTN_RIGHT`, `BTN_MIDDLE`) dos
+///   botões do tablet vão para o [`VMouse`], após as teclas já acumuladas
+///   (ex: `Ctrl` do zoom); nas ações da caneta (ex: `pen_touch`) continuam
+///   no [`VBtn`];
+/// - Demais eventos de botão vão para o [`VBtn`], agrupados em um frame
+///   (`SYN_REPORT`) por transição de botão (pressionar/soltar uma combinação).
+pub struct EmitDispatcher {
+    /// Caneta, dispositivos de botões, mouse e pad em uso
+    devices: Arc<VirtualDevices>,
+    /// Teclas do frame em construção, reutilizado entre chamadas
+    key_frame: Vec<InputEvent>,
+    /// Empréstimo dos dispositivos, devolvido ao [`DevicePool`](crate::virtual_device::DevicePool)
+    /// junto com o despachante
+    lease: Option<DeviceLease>,
+}
+
+impl EmitDispatcher {
+    /// Cria o despachante a partir de dispositivos virtuais já existentes.
+    pub fn new(vpen: VPen, vbtn: VBtn, vmouse: VMouse) -> Self {
+        Self {
+            devices: Arc::new(VirtualDevices { vpen, vbtn, vmouse, vpad: None }),
+            key_frame: Vec::with_capacity(16),
+            lease: None,
+        }
+    }
+
+    /// Cria o despachante com dispositivos emprestados de um [`DevicePool`](crate::virtual_device::DevicePool).
+    ///
+    /// Se o *pool* recriar os dispositivos (ex: novas teclas na configuração),
+    /// o despachante passa a usar os novos no próximo pacote.
+    pub fn from_lease(lease: DeviceLease) -> Self {
+        Self {
+            devices: lease.devices(),
+            key_frame: Vec::with_capacity(16),
+            lease: Some(lease),
+        }
+    }
+
+    /// Cria os dispositivos virtuais descritos pela configuração, anunciando
+    /// as teclas `keys` nos dispositivos de botões e de pad.
+    pub fn from_config(cfg: &Config, keys: &[Key]) -> Result<Self> {
+        let devices = VirtualDevices::create(&DeviceCapabilities::from_config(cfg, keys))?;
+        Ok(Self {
+            devices: Arc::new(devices),
+            key_frame: Vec::with_capacity(16),
+            lease: None,
+        })
+    }
+
+    /// Passa a usar os dispositivos atuais do empréstimo, se tiverem sido recriados.
+    fn refresh_devices(&mut self) {
+        if let Some(lease) = &self.lease
+            && lease.changed(&self.devices)
+        {
+            self.devices = lease.devices();
+        }
+    }
+
+    /// Emite os comandos nos dispositivos virtuais, na ordem recebida.
+    ///
+    /// Erros de emissão são registrados e não interrompem os comandos seguintes.
+    pub fn dispatch(&mut self, commands: &[EmitCommand]) {
+        self.refresh_devices();
+
+        // Cada transição de botão (pressionar/soltar uma combinação) vira um frame
+        let mut frame_owner: Option<(usize, bool)> = None;
+
+        for emit in commands {
+            match *emit {
+                EmitCommand::Pen { x, y, pressure, touch } => {
+                    if let Err(e) = self.devices.vpen.emit(x, y, pressure, touch) {
+                        eprintln!("Erro emitindo evento: {e}");
+                    }
+                }
+                EmitCommand::Proximity { in_range } => {
+                    // A entrada é sinalizada pelo próprio evento de caneta
+                    if !in_range && let Err(e) = self.devices.vpen.leave() {
+                        eprintln!("Erro emitindo evento: {e}");
+                    }
+                }
+                EmitCommand::Tool { eraser } => {
+                    if let Err(e) = self.devices.vpen.set_tool(eraser) {
+                        eprintln!("Erro trocando a ferramenta da caneta: {e}");
+                    }
+                }
+                EmitCommand::Btn { key, pressed, .. } if VPen::handles(Key::new(key as u16)) => {
+                    if let Err(e) = self.devices.vpen.button(Key::new(key as u16), pressed) {
+                        eprintln!("Erro emitindo botão da caneta: {e}");
+                    }
+                }
+                EmitCommand::Scroll { horizontal, amount } => {
+                    self.flush_key_frame();
+                    if let Err(e) = self.devices.vmouse.scroll(horizontal, amount) {
+                        eprintln!("Erro emitindo rolagem: {e}");
+                    }
+                }
+                EmitCommand::Btn { key, pressed, index }
+                    if index < PEN_BUTTON_INDEX && VMouse::handles(Key::new(key as u16)) =>
+                {
+                    self.flush_key_frame();
+                    if let Err(e) = self.devices.vmouse.button(Key::new(key as u16), pressed) {
+                        eprintln!("Erro emitindo botão do mouse: {e}");
+                    }
+                }
+                EmitCommand::Btn { key, pressed, .. } if VPad::handles(Key::new(key as u16)) => {
+                    if let Some(vpad) = &self.devices.vpad
+                        && let Err(e) = vpad.button(Key::new(key as u16), pressed)
+                    {
+                        eprintln!("Erro emitindo botão do pad: {e}");
+                    }
+                }
+                EmitCommand::Btn { key, pressed, index } => {
+                    if frame_owner != Some((index, pressed)) {
+                        self.flush_key_frame();
+                        frame_owner = Some((index, pressed));
+                    }
+                    self.key_frame.push(InputEvent::new(
+                        EventType::KEY,
+                        key as u16,
+                        pressed as i32,
+                    ));
+                }
+            }
+        }
+
+        self.flush_key_frame();
+    }
+
+    /// Emite as teclas acumuladas como um único frame do dispositivo de botões.
+    fn flush_key_frame(&mut self) {
+        if self.key_frame.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.devices.vbtn.emit_frame(&self.key_frame) {
+            eprintln!("Erro emitindo botão: {e}");
+        }
+        self.key_frame.clear();
+    }
+}
@@ -1,2 +1,6 @@
 pub mod device;
-pub use device::{VPen, VBtn};
+pub mod dispatcher;
+pub mod pool;
+pub use device::{VPen, VBtn, VPad, VMouse, WHEEL_DETENT};
+pub use dispatcher::EmitDispatcher;
+pub use pool::{DeviceCapabilities, DeviceLease, DevicePool, VirtualDevices};
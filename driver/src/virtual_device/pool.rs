@@ -0,0 +1,433 @@
+use anyhow::Result;
+use arc_swap::ArcSwap;
+use evdev::Key;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use table_z_config::Config;
+
+use crate::virtual_device::{VBtn, VMouse, VPad, VPen};
+
+/// Capacidades dos dispositivos virtuais derivadas da configuração.
+///
+/// Dispositivos com as mesmas capacidades são reaproveitados; qualquer
+/// diferença exige recriá-los.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceCapabilities {
+    pub name: String,
+    pub max_x: i32,
+    pub max_y: i32,
+    pub max_pressure: i32,
+    pub resolution_x: i32,
+    pub resolution_y: i32,
+    pub direct: bool,
+    /// Anuncia a borracha na caneta
+    pub eraser: bool,
+    /// Teclas anunciadas pelo dispositivo de botões, em ordem crescente
+    pub keys: Vec<Key>,
+    /// Botões do dispositivo de pad (`BTN_0` até o maior usado); vazio se
+    /// nenhum botão estiver no modo pad
+    pub pad_buttons: Vec<Key>,
+}
+
+impl DeviceCapabilities {
+    /// Extrai as capacidades da configuração.
+    ///
+    /// `keys` são as teclas que o tradutor pode emitir; as que pertencem à
+    /// caneta ([`VPen::handles`]), ao pad ([`VPad::handles`]) ou ao mouse
+    /// ([`VMouse::handles`]) não são anunciadas no dispositivo de botões, e
+    /// `BTN_TOOL_RUBBER` habilita a borracha na caneta. Um botão de mouse usado
+    /// em `pen_touch` continua no dispositivo de botões, que o emite.
+    pub fn from_config(cfg: &Config, keys: &[Key]) -> Self {
+        let (resolution_x, resolution_y) = cfg.pen.resolution();
+
+        let eraser = keys.contains(&Key::BTN_TOOL_RUBBER);
+        let pen_touch = Key::from_str(cfg.actions.pen_touch.trim()).ok();
+
+        // O pad anuncia uma faixa contínua, mantendo a numeração dos botões estável
+        let pad_count = keys
+            .iter()
+            .filter(|key| VPad::handles(**key))
+            .map(|key| (key.code() - Key::BTN_0.code()) as usize + 1)
+            .max()
+            .unwrap_or(0);
+        let pad_buttons = (0..pad_count).filter_map(VPad::button_key).collect();
+
+        let mut keys: Vec<Key> = keys
+            .iter()
+            .copied()
+            .filter(|key| {
+                !VPen::handles(*key)
+                    && !VPad::handles(*key)
+                    && (!VMouse::handles(*key) || Some(*key) == pen_touch)
+                    && *key != Key::BTN_TOOL_RUBBER
+            })
+            .collect();
+        keys.sort_by_key(|key| key.code());
+        keys.dedup();
+
+        Self {
+            name: cfg.xinput_name.clone(),
+            max_x: cfg.pen.max_x as i32,
+            max_y: cfg.pen.max_y as i32,
+            max_pressure: cfg.pen.max_pressure as i32,
+            resolution_x: resolution_x as i32,
+            resolution_y: resolution_y as i32,
+            direct: cfg.pen.direct,
+            eraser,
+            keys,
+            pad_buttons,
+        }
+    }
+
+    /// Retorna `true` se a caneta puder ser mantida ao passar para `other`.
+    fn same_pen(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.max_x == other.max_x
+            && self.max_y == other.max_y
+            && self.max_pressure == other.max_pressure
+            && self.resolution_x == other.resolution_x
+            && self.resolution_y == other.resolution_y
+            && self.direct == other.direct
+            && self.eraser == other.eraser
+    }
+
+    /// Nome do dispositivo de pad.
+    fn pad_name(&self) -> String {
+        format!("{} Pad", self.name)
+    }
+
+    /// Nome do dispositivo de mouse.
+    fn mouse_name(&self) -> String {
+        format!("{} Mouse", self.name)
+    }
+}
+
+/// Dispositivos virtuais de caneta, de botões, de mouse e, opcionalmente, de
+/// pad de um tablet.
+#[derive(Clone)]
+pub struct VirtualDevices {
+    pub vpen: VPen,
+    pub vbtn: VBtn,
+    pub vmouse: VMouse,
+    /// Criado apenas se algum botão estiver no modo pad
+    pub vpad: Option<VPad>,
+}
+
+impl VirtualDevices {
+    /// Cria os dispositivos `uinput` com as capacidades informadas.
+    pub fn create(caps: &DeviceCapabilities) -> Result<Self> {
+        Ok(Self {
+            vpen: Self::create_pen(caps)?,
+            vbtn: VBtn::new(&caps.keys, &caps.name)?,
+            vmouse: VMouse::new(&caps.mouse_name())?,
+            vpad: Self::create_pad(caps)?,
+        })
+    }
+
+    /// Cria o dispositivo de pad, se houver botões no modo pad.
+    fn create_pad(caps: &DeviceCapabilities) -> Result<Option<VPad>> {
+        if caps.pad_buttons.is_empty() {
+            return Ok(None);
+        }
+        VPad::new(&caps.pad_buttons, &caps.pad_name()).map(Some)
+    }
+
+    /// Cria o dispositivo virtual de caneta.
+    fn create_pen(caps: &DeviceCapabilities) -> Result<VPen> {
+        VPen::new(
+            caps.max_x,
+            caps.max_y,
+            caps.max_pressure,
+            caps.resolution_x,
+            caps.resolution_y,
+            caps.direct,
+            caps.eraser,
+            &caps.name,
+        )
+    }
+
+    /// Recria apenas os dispositivos cujas capacidades mudaram de `old` para `new`.
+    fn rebuild(&self, old: &DeviceCapabilities, new: &DeviceCapabilities) -> Result<Self> {
+        let vpen = if old.same_pen(new) {
+            self.vpen.clone()
+        } else {
+            Self::create_pen(new)?
+        };
+        let vbtn = if old.name == new.name && old.keys == new.keys {
+            self.vbtn.clone()
+        } else {
+            VBtn::new(&new.keys, &new.name)?
+        };
+        let vmouse = if old.name == new.name {
+            self.vmouse.clone()
+        } else {
+            VMouse::new(&new.mouse_name())?
+        };
+        let vpad = if old.name == new.name && old.pad_buttons == new.pad_buttons {
+            self.vpad.clone()
+        } else {
+            Self::create_pad(new)?
+        };
+        Ok(Self { vpen, vbtn, vmouse, vpad })
+    }
+
+    /// Retira a caneta de proximidade, volta para a ponta e solta as teclas e
+    /// os botões de mouse e de pad pressionados.
+    pub fn reset(&self) {
+        if let Err(e) = self.vpen.leave().and_then(|()| self.vpen.set_tool(false)) {
+            eprintln!("Erro liberando a caneta virtual: {e}");
+        }
+        if let Err(e) = self.vbtn.release_all() {
+            eprintln!("Erro liberando os botões virtuais: {e}");
+        }
+        if let Err(e) = self.vmouse.release_all() {
+            eprintln!("Erro liberando o mouse virtual: {e}");
+        }
+        if let Some(vpad) = &self.vpad
+            && let Err(e) = vpad.release_all()
+        {
+            eprintln!("Erro liberando os botões do pad: {e}");
+        }
+    }
+}
+
+/// Dispositivos mantidos pelo [`DevicePool`].
+struct PoolEntry<D> {
+    id: u64,
+    caps: DeviceCapabilities,
+    /// Dispositivos atuais; trocados quando as capacidades mudam
+    devices: Arc<ArcSwap<D>>,
+    in_use: bool,
+}
+
+/// Controle das entradas do [`DevicePool`], independente dos dispositivos em si.
+struct PoolState<D> {
+    entries: Vec<PoolEntry<D>>,
+    next_id: u64,
+}
+
+impl<D> Default for PoolState<D> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<D> PoolState<D> {
+    /// Marca como em uso uma entrada livre com as capacidades `caps`, se houver.
+    ///
+    /// Entradas livres com outras capacidades são descartadas.
+    fn claim(&mut self, caps: &DeviceCapabilities) -> Option<(u64, Arc<ArcSwap<D>>)> {
+        // Dispositivos livres de uma configuração anterior não servem mais
+        self.entries.retain(|e| e.in_use || e.caps == *caps);
+
+        let entry = self.entries.iter_mut().find(|e| !e.in_use)?;
+        entry.in_use = true;
+        Some((entry.id, entry.devices.clone()))
+    }
+
+    /// Acrescenta uma entrada em uso com os dispositivos recém-criados.
+    fn insert(&mut self, caps: DeviceCapabilities, devices: D) -> (u64, Arc<ArcSwap<D>>) {
+        let devices = Arc::new(ArcSwap::from_pointee(devices));
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(PoolEntry {
+            id,
+            caps,
+            devices: devices.clone(),
+            in_use: true,
+        });
+        (id, devices)
+    }
+
+    /// Libera a entrada `id` para o próximo empréstimo.
+    fn release(&mut self, id: u64) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) {
+            entry.in_use = false;
+        }
+    }
+}
+
+/// Conjunto de dispositivos virtuais mantidos entre reconexões.
+///
+/// Criar e destruir dispositivos `uinput` a cada conexão faz aplicações como
+/// Krita e GIMP verem dispositivos surgirem e sumirem (e acumularem duplicatas)
+/// quando o cabo oscila. O *pool* cria os dispositivos uma vez e os empresta a
+/// cada sessão; ao fim da sessão eles ficam livres para a próxima conexão.
+/// Só são recriados quando as capacidades mudam.
+#[derive(Clone, Default)]
+pub struct DevicePool {
+    state: Arc<Mutex<PoolState<VirtualDevices>>>,
+}
+
+impl DevicePool {
+    /// Cria um *pool* vazio.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empresta dispositivos com as capacidades informadas.
+    ///
+    /// Reaproveita dispositivos livres compatíveis; dispositivos livres com
+    /// capacidades diferentes são destruídos. Cada sessão ativa recebe os
+    /// seus próprios dispositivos.
+    pub fn acquire(&self, caps: DeviceCapabilities) -> Result<DeviceLease> {
+        let mut state = self.state.lock().unwrap();
+
+        let (id, devices) = match state.claim(&caps) {
+            Some(entry) => entry,
+            None => {
+                println!("Criando dispositivos virtuais \"{}\"", caps.name);
+                let devices = VirtualDevices::create(&caps)?;
+                state.insert(caps, devices)
+            }
+        };
+
+        Ok(DeviceLease {
+            pool: self.clone(),
+            id,
+            devices,
+        })
+    }
+
+    /// Aplica novas capacidades aos dispositivos em uso, recriando apenas os
+    /// que mudaram (ex: a configuração passou a usar novas teclas).
+    ///
+    /// Os despachantes das sessões passam a usar os novos dispositivos no
+    /// próximo pacote. Dispositivos livres são recriados no próximo empréstimo.
+    pub fn reconfigure(&self, caps: &DeviceCapabilities) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|e| e.in_use || e.caps == *caps);
+
+        for entry in state.entries.iter_mut().filter(|e| e.caps != *caps) {
+            let current = entry.devices.load_full();
+            match current.rebuild(&entry.caps, caps) {
+                Ok(devices) => {
+                    println!("Dispositivos virtuais \"{}\" recriados", caps.name);
+                    current.reset();
+                    entry.devices.store(Arc::new(devices));
+                    entry.caps = caps.clone();
+                }
+                Err(e) => eprintln!("Erro recriando dispositivos virtuais: {e:#}"),
+            }
+        }
+    }
+
+    /// Devolve os dispositivos emprestados.
+    fn release(&self, id: u64) {
+        self.state.lock().unwrap().release(id);
+    }
+}
+
+/// Empréstimo de dispositivos do [`DevicePool`], devolvidos ao ser descartado.
+///
+/// Ao devolver, a caneta sai de proximidade e as teclas pressionadas são
+/// soltas, para que uma desconexão no meio de um traço não deixe estado preso.
+pub struct DeviceLease {
+    pool: DevicePool,
+    id: u64,
+    devices: Arc<ArcSwap<VirtualDevices>>,
+}
+
+impl DeviceLease {
+    /// Dispositivos emprestados atuais.
+    pub fn devices(&self) -> Arc<VirtualDevices> {
+        self.devices.load_full()
+    }
+
+    /// Retorna `true` se os dispositivos foram recriados desde `seen`.
+    pub fn changed(&self, seen: &Arc<VirtualDevices>) -> bool {
+        !Arc::ptr_eq(&self.devices.load(), seen)
+    }
+}
+
+impl Drop for DeviceLease {
+    fn drop(&mut self) {
+        self.devices.load().reset();
+        self.pool.release(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps(name: &str) -> DeviceCapabilities {
+        DeviceCapabilities {
+            name: name.into(),
+            max_x: 4096,
+            max_y: 4096,
+            max_pressure: 2048,
+            resolution_x: 20,
+            resolution_y: 30,
+            direct: false,
+            eraser: false,
+            keys: vec![Key::KEY_A],
+            pad_buttons: Vec::new(),
+        }
+    }
+
+    fn config(pen_touch: &str) -> Config {
+        serde_yaml::from_str(&format!(
+            "xinput_name: mesa
+vendor_id: 0x08f2
+product_id: 0x6811
+endpoint: 0x85
+pen: {{ max_x: 4096, max_y: 4096, max_pressure: 2047, resolution_x: 20, resolution_y: 30 }}
+actions: {{ pen: BTN_TOOL_PEN, stylus: BTN_STYLUS, pen_touch: {pen_touch}, tablet_buttons: [] }}
+settings:
+  swap_axis: false
+  swap_direction_x: false
+  swap_direction_y: false
+  touch_threshold: 0
+  touch_hysteresis: 0
+"
+        ))
+        .expect("configuração de teste")
+    }
+
+    #[test]
+    fn mouse_buttons_of_tablet_buttons_are_not_announced() {
+        let caps = DeviceCapabilities::from_config(&config("None"), &[Key::BTN_LEFT, Key::KEY_Z]);
+        assert_eq!(caps.keys, vec![Key::KEY_Z]);
+    }
+
+    #[test]
+    fn pen_touch_mouse_button_stays_on_button_device() {
+        let keys = [Key::BTN_STYLUS, Key::BTN_LEFT, Key::BTN_RIGHT];
+        let caps = DeviceCapabilities::from_config(&config("BTN_LEFT"), &keys);
+        assert_eq!(caps.keys, vec![Key::BTN_LEFT]);
+    }
+
+    #[test]
+    fn released_entry_is_reused() {
+        let mut state = PoolState::<u32>::default();
+        assert!(state.claim(&caps("mesa")).is_none());
+        let (id, devices) = state.insert(caps("mesa"), 1);
+
+        state.release(id);
+        let (again, reused) = state.claim(&caps("mesa")).expect("entrada livre");
+        assert_eq!(again, id);
+        assert!(Arc::ptr_eq(&devices, &reused));
+        assert_eq!(state.entries.len(), 1);
+    }
+
+    #[test]
+    fn entry_in_use_is_not_shared() {
+        let mut state = PoolState::<u32>::default();
+        state.insert(caps("mesa"), 1);
+        assert!(state.claim(&caps("mesa")).is_none());
+    }
+
+    #[test]
+    fn free_entry_with_other_caps_is_dropped() {
+        let mut state = PoolState::<u32>::default();
+        let (id, _) = state.insert(caps("mesa"), 1);
+        state.release(id);
+
+        assert!(state.claim(&caps("outra")).is_none());
+        assert!(state.entries.is_empty());
+    }
+}
@@ -1,5 +1,5 @@
-use serde::{Deserialize, Serialize};
-use std::{fs, path::Path};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{fs, io::{self, Write}, path::Path};
 use std::error::Error;
 
 /// Representa a configuração principal do dispositivo/tablet.
@@ -12,13 +12,16 @@ use std::error::Error;
 /// xinput_name: "Tablet M100"
 /// vendor_id: 1234
 /// product_id: 5678
-/// interface: 1
+/// endpoint: 0x81  # opcional; detectado automaticamente se ausente
 /// pen:
 ///   max_x: 32767
 ///   max_y: 32767
 ///   max_pressure: 8192
-///   resolution_x: 100
+///   resolution_x: 100  # unidades/mm
 ///   resolution_y: 100
+///   # width_mm: 216     # opcional; calcula a resolução a partir da área ativa
+///   # height_mm: 135
+///   direct: false       # true para mesas com tela
 /// actions:
 ///   pen: "BTN_LEFT"
 ///   stylus: "BTN_RIGHT"
@@ -30,6 +33,38 @@ use std::error::Error;
 ///   swap_axis: false
 ///   swap_direction_x: false
 ///   swap_direction_y: false
+///   touch_threshold: 120
+///   touch_hysteresis: 40
+/// init:
+///   - type: feature_report
+///     report_id: 2
+///     data: [2, 2, 0, 0, 0, 0, 0, 0]
+///     delay_ms: 50
+///   - type: control
+///     request_type: 0x21
+///     request: 0x09
+///     value: 0x0308
+///     index: 2
+///     data: [8, 4, 29, 1, 255, 255, 6, 46]
+///     optional: true
+/// source:
+///   backend: hidraw
+/// capture:
+///   enabled: false
+///   path: "/tmp/tablet_capture.tzcap"
+///   max_file_size: 8388608
+///   max_files: 3
+/// stats:
+///   log_interval_secs: 60
+/// hotplug:
+///   backend: libusb  # libusb | poll | udev
+///   poll_interval_ms: 1000
+/// button_map:
+///   report_id: 2
+///   offsets: [1, 3]
+///   buttons:
+///     - [1, 86]
+///     - [1, 87]
 /// ```
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
@@ -40,8 +75,24 @@ pub struct Config {
     pub vendor_id: u16,
     pub product_id: u16,
 
-    /// Interface (endpoint) usada para leitura USB.
-    pub interface: u8,
+    /// Endpoint de interrupção usado para leitura USB (ex: `0x85`).
+    ///
+    /// Opcional: se ausente (ou `0`), o driver escolhe automaticamente o endpoint
+    /// de interrupção IN das interfaces HID do dispositivo. Aceita também o nome
+    /// antigo `interface`.
+    #[serde(
+        default,
+        alias = "interface",
+        deserialize_with = "deserialize_endpoint",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub endpoint: Option<u8>,
+
+    /// Lista de endpoints lidos em conjunto, para tablets que enviam caneta e
+    /// botões por interfaces diferentes (ex: `[0x81, 0x82]`). Quando presente,
+    /// substitui `endpoint`; os pacotes são mesclados em um único fluxo.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<u8>,
 
     /// Configurações físicas da caneta.
     pub pen: PenConfig,
@@ -51,6 +102,31 @@ pub struct Config {
 
     /// Ajustes de eixos e transformações.
     pub settings: SettingsConfig,
+
+    /// Sequência de inicialização enviada ao dispositivo ao conectar (apenas libusb).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub init: Vec<InitStep>,
+
+    /// Origem dos pacotes HID (libusb ou hidraw).
+    #[serde(default)]
+    pub source: SourceConfig,
+
+    /// Captura dos pacotes USB crus para depuração.
+    #[serde(default)]
+    pub capture: CaptureConfig,
+
+    /// Registro periódico das estatísticas de entrada.
+    #[serde(default)]
+    pub stats: StatsConfig,
+
+    /// Detecção de conexão e remoção de dispositivos.
+    #[serde(default)]
+    pub hotplug: HotplugConfig,
+
+    /// Tabela de identificação dos botões do tablet, normalmente gerada pelo
+    /// modo de aprendizado. Se ausente, o tradutor usa o mapeamento embutido.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub button_map: Option<ButtonMapConfig>,
 }
 
 /// Define os parâmetros físicos da caneta (limites e resolução).
@@ -59,8 +135,39 @@ pub struct PenConfig {
     pub max_x: u32,
     pub max_y: u32,
     pub max_pressure: u32,
+    /// Resolução do eixo X, em unidades por milímetro.
     pub resolution_x: u32,
+    /// Resolução do eixo Y, em unidades por milímetro.
     pub resolution_y: u32,
+
+    /// Largura da área ativa, em milímetros. Se definida, a resolução do eixo X
+    /// é calculada como `max_x / width_mm`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width_mm: Option<u32>,
+
+    /// Altura da área ativa, em milímetros. Se definida, a resolução do eixo Y
+    /// é calculada como `max_y / height_mm`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height_mm: Option<u32>,
+
+    /// `true` para mesas com tela (caneta aponta diretamente na tela,
+    /// `INPUT_PROP_DIRECT`); `false` para mesas opacas (`INPUT_PROP_POINTER`).
+    #[serde(default)]
+    pub direct: bool,
+}
+
+impl PenConfig {
+    /// Resolução efetiva `(x, y)` em unidades por milímetro.
+    pub fn resolution(&self) -> (u32, u32) {
+        let derive = |max: u32, mm: Option<u32>, fallback: u32| match mm {
+            Some(mm) if mm > 0 => (max / mm).max(1),
+            _ => fallback,
+        };
+        (
+            derive(self.max_x, self.width_mm, self.resolution_x),
+            derive(self.max_y, self.height_mm, self.resolution_y),
+        )
+    }
 }
 
 /// Define o mapeamento das ações e botões configuráveis.
@@ -76,6 +183,12 @@ pub struct ActionsConfig {
     pub pen_touch: String,
 
     /// Lista de combinações de botões físicos no tablet.
+    ///
+    /// Além de combinações de teclas, aceita `ERASER` (borracha enquanto o
+    /// botão estiver pressionado), `ERASER_TOGGLE` (alterna caneta/borracha),
+    /// `PAD` (botão `BTN_0`..`BTN_7` no dispositivo de pad, apenas nos 8
+    /// primeiros botões), `SCROLL_UP`/`SCROLL_DOWN`/`SCROLL_LEFT`/`SCROLL_RIGHT` e `ZOOM_IN`/`ZOOM_OUT` (`Ctrl` + roda).
+    /// `BTN_LEFT`, `BTN_RIGHT` e `BTN_MIDDLE` são emitidos pelo mouse virtual.
     pub tablet_buttons: Vec<String>,
 }
 
@@ -90,6 +203,205 @@ pub struct SettingsConfig {
 
     /// Inverte a direção do eixo Y.
     pub swap_direction_y: bool,
+
+    /// Pressão mínima para ativar o toque (clique) da caneta.
+    ///
+    /// `0` mantém o comportamento do hardware (contato indicado pelo próprio pacote).
+    #[serde(default)]
+    pub touch_threshold: u32,
+
+    /// Histerese da liberação do toque: o toque só é liberado quando a pressão
+    /// cai abaixo de `touch_threshold - touch_hysteresis`.
+    #[serde(default)]
+    pub touch_hysteresis: u32,
+}
+
+/// Define como identificar cada botão físico a partir dos pacotes HID.
+///
+/// Um pacote corresponde ao botão `i` quando `buf[0] == report_id` e os bytes
+/// nas posições `offsets` são iguais a `buttons[i]`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ButtonMapConfig {
+    /// ID do relatório (primeiro byte) dos pacotes de botões.
+    pub report_id: u8,
+
+    /// Posições dos bytes que distinguem os botões.
+    pub offsets: Vec<usize>,
+
+    /// Valores esperados em `offsets` para cada botão, na ordem de `tablet_buttons`.
+    pub buttons: Vec<Vec<u8>>,
+}
+
+/// Passo da sequência de inicialização, enviado após o *claim* da interface.
+///
+/// Alguns tablets só enviam dados de caneta em resolução total após receber
+/// um comando específico do fabricante ao serem conectados.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InitStep {
+    /// Transferência a enviar.
+    #[serde(flatten)]
+    pub kind: InitStepKind,
+
+    /// Espera após o passo, em milissegundos.
+    #[serde(default)]
+    pub delay_ms: u64,
+
+    /// Se `true`, uma falha neste passo é apenas registrada e a sequência continua.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// Tipo de transferência de um [`InitStep`].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InitStepKind {
+    /// Transferência de controle genérica.
+    ///
+    /// Com o bit de direção (`0x80`) em `request_type`, lê `length` bytes;
+    /// caso contrário, envia `data`.
+    Control {
+        request_type: u8,
+        request: u8,
+        value: u16,
+        index: u16,
+        #[serde(default)]
+        data: Vec<u8>,
+        #[serde(default)]
+        length: usize,
+    },
+
+    /// HID SET_REPORT de um *feature report* na interface lida.
+    ///
+    /// `data` é enviado como está (incluindo o report ID, se o dispositivo usar IDs).
+    FeatureReport { report_id: u8, data: Vec<u8> },
+}
+
+/// Backend de leitura dos pacotes HID.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceBackend {
+    /// Leitura direta via libusb; desanexa o driver HID do kernel (requer root).
+    #[default]
+    Libusb,
+    /// Leitura de `/dev/hidrawN`, mantendo o driver do kernel anexado.
+    Hidraw,
+}
+
+/// Define de onde os pacotes HID são lidos.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct SourceConfig {
+    /// Backend de leitura.
+    #[serde(default)]
+    pub backend: SourceBackend,
+
+    /// Caminho fixo do nó hidraw (ou de um pipe de teste). Se ausente, o nó é
+    /// localizado via sysfs pelo `vendor_id`/`product_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Define o registro periódico das estatísticas de entrada no terminal.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct StatsConfig {
+    /// Intervalo entre registros, em segundos (`0` desativa).
+    #[serde(default)]
+    pub log_interval_secs: u64,
+}
+
+/// Backend de detecção de dispositivos (hotplug).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HotplugBackend {
+    /// Eventos de hotplug da libusb; usa `poll` se não houver suporte.
+    #[default]
+    Libusb,
+    /// Enumeração periódica dos dispositivos (containers, libusb sem hotplug).
+    Poll,
+    /// Eventos do kernel via netlink; conexões são sinalizadas após o
+    /// processamento pelo udev (permissões das regras já aplicadas).
+    Udev,
+}
+
+/// Define a detecção de conexão e remoção de dispositivos.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HotplugConfig {
+    /// Backend de detecção.
+    #[serde(default)]
+    pub backend: HotplugBackend,
+
+    /// Intervalo entre enumerações do backend `poll`, em milissegundos.
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+fn default_poll_interval_ms() -> u64 {
+    1000
+}
+
+impl Default for HotplugConfig {
+    fn default() -> Self {
+        Self {
+            backend: HotplugBackend::default(),
+            poll_interval_ms: default_poll_interval_ms(),
+        }
+    }
+}
+
+/// Define a captura dos pacotes HID crus recebidos do dispositivo.
+///
+/// Os pacotes são gravados em formato binário compacto, com rotação
+/// quando o arquivo atinge `max_file_size` bytes.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CaptureConfig {
+    /// Ativa a captura.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Caminho do arquivo de captura atual.
+    ///
+    /// Lido apenas na inicialização; configurações recebidas pelo socket não o alteram.
+    #[serde(default = "default_capture_path")]
+    pub path: String,
+
+    /// Tamanho máximo de cada arquivo, em bytes.
+    #[serde(default = "default_capture_max_file_size")]
+    pub max_file_size: u64,
+
+    /// Quantidade de arquivos antigos mantidos na rotação (`arquivo.1`, `arquivo.2`, ...).
+    #[serde(default = "default_capture_max_files")]
+    pub max_files: u32,
+}
+
+/// Interpreta o endpoint configurado; `0` (endpoint de controle) equivale a "automático".
+fn deserialize_endpoint<'de, D>(deserializer: D) -> Result<Option<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let endpoint = Option::<u8>::deserialize(deserializer)?;
+    Ok(endpoint.filter(|ep| *ep != 0))
+}
+
+fn default_capture_path() -> String {
+    "/tmp/tablet_capture.tzcap".into()
+}
+
+fn default_capture_max_file_size() -> u64 {
+    8 * 1024 * 1024
+}
+
+fn default_capture_max_files() -> u32 {
+    3
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: default_capture_path(),
+            max_file_size: default_capture_max_file_size(),
+            max_files: default_capture_max_files(),
+        }
+    }
 }
 
 impl Config {
@@ -103,7 +415,9 @@ impl Config {
     /// - `Err` se o arquivo não existir, estiver ilegível ou mal formatado.
     ///
     /// # Exemplo
-    /// ```
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use table_z_config::Config;
     /// let cfg = Config::from_file(Path::new("/etc/table_z_utils.yaml")).unwrap();
     /// println!("Nome do dispositivo: {}", cfg.xinput_name);
     /// ```
@@ -123,4 +437,187 @@ impl Config {
 
         Ok(cfg)
     }
+
+    /// Grava a configuração em um arquivo YAML.
+    ///
+    /// # Erros
+    /// Retorna `Err` se a configuração não puder ser serializada ou o arquivo gravado.
+    pub fn save_to_file(&self, path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let content = serde_yaml::to_string(self)
+            .map_err(|e| format!("Erro ao gerar YAML: {e}"))?;
+
+        write_atomic(path, &content)
+            .map_err(|e| format!("Erro ao gravar arquivo {}: {e}", path.display()))?;
+
+        Ok(())
+    }
+
+    /// Grava a tabela de botões no arquivo YAML em `path`, alterando apenas a
+    /// chave `button_map`.
+    ///
+    /// O restante do arquivo (comentários, valores em hexadecimal, ordem das
+    /// chaves) é mantido como está. O resultado é validado antes de substituir
+    /// o arquivo, e a substituição é atômica.
+    ///
+    /// # Erros
+    /// Retorna `Err` se o arquivo não puder ser lido ou gravado, ou se o
+    /// resultado não for uma configuração válida.
+    pub fn save_button_map(
+        path: &Path,
+        button_map: &ButtonMapConfig,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        #[derive(Serialize)]
+        struct ButtonMapEntry<'a> {
+            button_map: &'a ButtonMapConfig,
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Erro ao ler arquivo {}: {e}", path.display()))?;
+        let entry = serde_yaml::to_string(&ButtonMapEntry { button_map })
+            .map_err(|e| format!("Erro ao gerar YAML: {e}"))?;
+
+        let updated = replace_top_level_entry(&content, "button_map", &entry);
+        serde_yaml::from_str::<Config>(&updated)
+            .map_err(|e| format!("Configuração resultante inválida: {e}"))?;
+
+        write_atomic(path, &updated)
+            .map_err(|e| format!("Erro ao gravar arquivo {}: {e}", path.display()))?;
+
+        Ok(())
+    }
+
+    /// Retorna `true` se `other` identifica outro dispositivo ou outra forma de
+    /// acessá-lo (IDs, endpoints, origem dos pacotes ou sequência `init:`),
+    /// exigindo reconectar as sessões ativas.
+    pub fn device_changed(&self, other: &Config) -> bool {
+        self.vendor_id != other.vendor_id
+            || self.product_id != other.product_id
+            || self.endpoint != other.endpoint
+            || self.endpoints != other.endpoints
+            || self.source != other.source
+            || self.init != other.init
+    }
+}
+
+/// Substitui a entrada de nível superior `key` (a linha `key:` e as linhas
+/// indentadas seguintes) de um documento YAML por `entry`, ou a acrescenta ao
+/// final se não existir. As demais linhas são preservadas.
+fn replace_top_level_entry(content: &str, key: &str, entry: &str) -> String {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let is_key = |line: &str| {
+        line.strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with(':'))
+    };
+
+    let Some(start) = lines.iter().position(|line| is_key(line)) else {
+        let mut out = content.to_string();
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(entry);
+        return out;
+    };
+
+    // A entrada termina na última linha indentada antes da próxima chave
+    let mut end = start;
+    for (i, line) in lines.iter().enumerate().skip(start + 1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with([' ', '\t']) {
+            break;
+        }
+        end = i;
+    }
+
+    let mut out: String = lines[..start].concat();
+    out.push_str(entry);
+    out.push_str(&lines[end + 1..].concat());
+    out
+}
+
+/// Grava `content` em `path` sem risco de deixar o arquivo truncado: escreve
+/// um arquivo temporário no mesmo diretório e o renomeia sobre o original,
+/// mantendo as permissões do arquivo existente.
+fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "caminho sem nome de arquivo"))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    // Um temporário esquecido por uma gravação interrompida é descartado
+    match fs::remove_file(&tmp) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    let result = (|| {
+        let mut file = fs::OpenOptions::new().write(true).create_new(true).open(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+
+    if result.is_err() {
+        fs::remove_file(&tmp).ok();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRY: &str = "button_map:\n  report_id: 2\n";
+
+    #[test]
+    fn replaces_only_the_entry() {
+        let content = "# Mesa do estúdio\nvendor_id: 0x0b57\nbutton_map:\n  report_id: 1\n  offsets:\n  - 3\n\n# Ajustes\nsettings:\n  swap_axis: false\n";
+        assert_eq!(
+            replace_top_level_entry(content, "button_map", ENTRY),
+            "# Mesa do estúdio\nvendor_id: 0x0b57\nbutton_map:\n  report_id: 2\n\n# Ajustes\nsettings:\n  swap_axis: false\n"
+        );
+    }
+
+    #[test]
+    fn appends_missing_entry() {
+        assert_eq!(
+            replace_top_level_entry("vendor_id: 0x0b57", "button_map", ENTRY),
+            "vendor_id: 0x0b57\nbutton_map:\n  report_id: 2\n"
+        );
+    }
+
+    #[test]
+    fn write_atomic_replaces_file_keeping_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("table_z_config_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("table_z_utils.yaml");
+        fs::write(&path, "antigo").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        write_atomic(&path, "novo").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "novo");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn ignores_keys_with_the_same_prefix() {
+        let content = "button_map_old: 1\n";
+        assert_eq!(
+            replace_top_level_entry(content, "button_map", ENTRY),
+            "button_map_old: 1\nbutton_map:\n  report_id: 2\n"
+        );
+    }
 }
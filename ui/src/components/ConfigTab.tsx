@@ -3,6 +3,12 @@ import { invoke } from "@tauri-apps/api/core";
 
 const KEY_OPTIONS = [
   "None",
+  "ERASER",
+  "ERASER_TOGGLE",
+  "PAD",
+  "SCROLL_UP", "SCROLL_DOWN", "SCROLL_LEFT", "SCROLL_RIGHT",
+  "ZOOM_IN", "ZOOM_OUT",
+  "BTN_LEFT", "BTN_RIGHT", "BTN_MIDDLE",
   "KEY_LEFTCTRL",
   "KEY_LEFTSHIFT",
   "KEY_LEFTALT",
@@ -18,10 +24,14 @@ type Config = {
     xinput_name: String;
     vendor_id: number;
     product_id: number;
-    interface: number;
+    endpoint?: number;
+    endpoints?: Array<number>;
     pen: PenConfig;
     actions: ActionsConfig;
     settings: SettingsConfig;
+    source?: SourceConfig;
+    hotplug?: HotplugConfig;
+    capture?: CaptureConfig;
 }
 
 type PenConfig = {
@@ -30,6 +40,9 @@ type PenConfig = {
     max_pressure: number;
     resolution_x: number;
     resolution_y: number;
+    width_mm?: number;
+    height_mm?: number;
+    direct?: boolean;
 }
 
 type ActionsConfig = {
@@ -43,6 +56,25 @@ type SettingsConfig = {
     swap_axis: boolean;
     swap_direction_x: boolean;
     swap_direction_y: boolean;
+    touch_threshold?: number;
+    touch_hysteresis?: number;
+}
+
+type SourceConfig = {
+    backend: "libusb" | "hidraw";
+    path?: string;
+}
+
+type HotplugConfig = {
+    backend: "libusb" | "poll" | "udev";
+    poll_interval_ms: number;
+}
+
+type CaptureConfig = {
+    enabled: boolean;
+    path: string;
+    max_file_size: number;
+    max_files: number;
 }
 
 function ConfigTab() {
@@ -50,7 +82,6 @@ function ConfigTab() {
     xinput_name: "",
     vendor_id: 0,
     product_id: 0,
-    interface: 0,
     pen: { max_x: 0, max_y: 0, max_pressure: 0, resolution_x: 0, resolution_y: 0 },
     actions: { pen: "", stylus: "", pen_touch: "", tablet_buttons: [] },
     settings: { swap_axis: false, swap_direction_x: false, swap_direction_y: false },